@@ -0,0 +1,26 @@
+extern crate cc;
+
+use std::fs;
+
+pub fn main() {
+    let version = fs::read_to_string("cadical/VERSION").expect("Cannot find 'cadical/VERSION'");
+    let files = fs::read_dir("cadical/src")
+        .expect("Cannot find 'cadical' directory")
+        .filter_map(Result::ok)
+        .filter(|p| p.file_name().to_string_lossy().ends_with(".cpp"))
+        .map(|p| p.path())
+        .collect::<Vec<_>>();
+
+    cc::Build::new()
+        .cpp(true)
+        .std("c++11")
+        .warnings(false)
+        .opt_level(3)
+        .define("NBUILD", None)
+        .define("NDEBUG", None)
+        .define("NTRACING", None)
+        .define("QUIET", None)
+        .define("VERSION", format!("\"{}\"", version.trim()).as_str())
+        .files(files)
+        .compile("cadical");
+}
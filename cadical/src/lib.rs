@@ -0,0 +1,273 @@
+//! A simple incremental wrapper for the CaDiCaL SAT solver.
+//!
+//! CaDiCaL is a CDCL SAT solver by Armin Biere and others. Unlike Kissat, it
+//! supports incremental solving through the IPASIR interface: clauses can be
+//! added between calls to `solve`, each call can assume literals that only
+//! hold for that call, and an unsatisfiable call reports which of its
+//! assumptions were needed to refute it.
+//!
+//! This crate builds the library sources of CaDiCaL together with its C
+//! interface, and provides a safe interface over it.
+//!
+//! ```rust
+//! extern crate cadical;
+//! fn main() {
+//!     let mut solver = cadical::Solver::new();
+//!     let a = solver.var();
+//!     let b = solver.var();
+//!     solver.add2(a, !b);
+//!     solver.assume(b);
+//!     match solver.solve() {
+//!         cadical::Outcome::Sat => println!("SAT: {:?} {:?}", solver.value(a), solver.value(b)),
+//!         _ => println!("UNSAT"),
+//!     }
+//!     solver.assume(!a);
+//!     solver.assume(b);
+//!     if let cadical::Outcome::Unsat = solver.solve() {
+//!         println!("failed: {} {}", solver.failed(!a), solver.failed(b));
+//!     }
+//! }
+//! ```
+
+use std::ops::Not;
+use std::os::raw::{c_int, c_void};
+
+const MAX_VAR_ID: c_int = c_int::MAX - 1;
+
+#[repr(C)]
+struct ccadical {
+    _unused: [u8; 0],
+}
+
+extern "C" {
+    fn ccadical_init() -> *mut ccadical;
+    fn ccadical_release(solver: *mut ccadical);
+    fn ccadical_add(solver: *mut ccadical, lit: c_int);
+    fn ccadical_assume(solver: *mut ccadical, lit: c_int);
+    fn ccadical_solve(solver: *mut ccadical) -> c_int;
+    fn ccadical_val(solver: *mut ccadical, lit: c_int) -> c_int;
+    fn ccadical_failed(solver: *mut ccadical, lit: c_int) -> c_int;
+    fn ccadical_set_terminate(
+        solver: *mut ccadical,
+        state: *mut c_void,
+        terminate: Option<extern "C" fn(state: *mut c_void) -> c_int>,
+    );
+}
+
+/// The result of a call to `solve`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// A model was found; read it with `value`.
+    Sat,
+    /// No model exists under the assumptions; see which ones with `failed`.
+    Unsat,
+    /// The search was stopped before it reached an answer.
+    Unknown,
+}
+
+/// A single literal or its negation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Var {
+    id: c_int,
+}
+
+impl Not for Var {
+    type Output = Var;
+
+    fn not(self) -> Var {
+        Var { id: -self.id }
+    }
+}
+
+/// An incremental SAT solver
+pub struct Solver {
+    p: *mut ccadical,
+    n_vars: c_int,
+    last: Option<Outcome>,
+}
+
+impl Solver {
+    /// Create a new solver instance
+    pub fn new() -> Solver {
+        unsafe {
+            let p = ccadical_init();
+            assert!(!p.is_null());
+            Solver { p, n_vars: 0, last: None }
+        }
+    }
+
+    /// Create a new literal
+    pub fn var(&mut self) -> Var {
+        assert!(self.n_vars < MAX_VAR_ID);
+        let id = self.n_vars + 1;
+        self.n_vars = id;
+        Var { id }
+    }
+
+    /// Assert a literal should be true in every later solution
+    pub fn add1(&mut self, a: Var) {
+        self.add(&[a]);
+    }
+
+    /// Assert `a or b` should be true in every later solution
+    pub fn add2(&mut self, a: Var, b: Var) {
+        self.add(&[a, b]);
+    }
+
+    /// Add an assertion for an arbitrarily long clause
+    pub fn add(&mut self, x: &[Var]) {
+        self.last = None;
+        unsafe {
+            for v in x {
+                ccadical_add(self.p, v.id);
+            }
+            ccadical_add(self.p, 0);
+        }
+    }
+
+    /// Assume a literal is true for the next call to `solve` only
+    pub fn assume(&mut self, a: Var) {
+        self.last = None;
+        unsafe {
+            ccadical_assume(self.p, a.id);
+        }
+    }
+
+    /// Solve the clauses added so far under the pending assumptions, which
+    /// are then cleared.
+    pub fn solve(&mut self) -> Outcome {
+        let ret = unsafe { ccadical_solve(self.p) };
+        self.outcome(ret)
+    }
+
+    /// Solve like `solve`, but give up with `Outcome::Unknown` once `stop`
+    /// returns true. CaDiCaL polls it regularly during the search.
+    pub fn solve_until<F: FnMut() -> bool>(&mut self, mut stop: F) -> Outcome {
+        extern "C" fn terminate<F: FnMut() -> bool>(state: *mut c_void) -> c_int {
+            let stop = unsafe { &mut *(state as *mut F) };
+            stop() as c_int
+        }
+
+        let ret = unsafe {
+            ccadical_set_terminate(self.p, &mut stop as *mut F as *mut c_void, Some(terminate::<F>));
+            let ret = ccadical_solve(self.p);
+            ccadical_set_terminate(self.p, std::ptr::null_mut(), None);
+            ret
+        };
+        self.outcome(ret)
+    }
+
+    fn outcome(&mut self, ret: c_int) -> Outcome {
+        let outcome = match ret {
+            10 => Outcome::Sat,
+            20 => Outcome::Unsat,
+            0 => Outcome::Unknown,
+            _ => unreachable!(),
+        };
+        self.last = Some(outcome);
+        outcome
+    }
+
+    /// Return the value of a literal in the model found by the last call to
+    /// `solve`, which must have been satisfiable with no clauses or
+    /// assumptions added since.
+    pub fn value(&self, x: Var) -> bool {
+        assert_eq!(self.last, Some(Outcome::Sat));
+        unsafe { ccadical_val(self.p, x.id) > 0 }
+    }
+
+    /// Return whether an assumption of the last call to `solve`, which must
+    /// have been unsatisfiable, was needed to refute it. The assumptions that
+    /// failed are unsatisfiable together, though not necessarily minimally.
+    pub fn failed(&self, x: Var) -> bool {
+        assert_eq!(self.last, Some(Outcome::Unsat));
+        unsafe { ccadical_failed(self.p, x.id) != 0 }
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Solver {
+        Solver::new()
+    }
+}
+
+impl Drop for Solver {
+    fn drop(&mut self) {
+        unsafe {
+            ccadical_release(self.p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Outcome, Solver, Var};
+
+    #[test]
+    fn sat() {
+        let mut solver = Solver::new();
+        let a = solver.var();
+        let b = solver.var();
+        solver.add(&[a, b]);
+        solver.add(&[a, !b]);
+        solver.add(&[!a, !b]);
+        assert_eq!(solver.solve(), Outcome::Sat);
+        assert!(solver.value(a));
+        assert!(!solver.value(b));
+    }
+
+    #[test]
+    fn unsat() {
+        let mut solver = Solver::new();
+        let a = solver.var();
+        let b = solver.var();
+        solver.add(&[a, b]);
+        solver.add(&[a, !b]);
+        solver.add(&[!a, !b]);
+        solver.add(&[b]);
+        assert_eq!(solver.solve(), Outcome::Unsat);
+    }
+
+    #[test]
+    fn assumptions_last_one_call() {
+        let mut solver = Solver::new();
+        let a = solver.var();
+        let b = solver.var();
+        let c = solver.var();
+        solver.add(&[a, b]);
+        solver.assume(!a);
+        solver.assume(!b);
+        solver.assume(c);
+        assert_eq!(solver.solve(), Outcome::Unsat);
+        assert!(solver.failed(!a));
+        assert!(solver.failed(!b));
+        assert!(!solver.failed(c));
+
+        // the same solver answers again once the assumptions are gone,
+        // and keeps clauses added in between
+        solver.assume(!a);
+        assert_eq!(solver.solve(), Outcome::Sat);
+        assert!(solver.value(b));
+        solver.add1(!b);
+        assert_eq!(solver.solve(), Outcome::Sat);
+        assert!(solver.value(a));
+    }
+
+    #[test]
+    fn terminate_gives_up() {
+        // ten pigeons in nine holes is unsatisfiable, but not quickly
+        let mut solver = Solver::new();
+        let holes: Vec<Vec<Var>> = (0..10).map(|_| (0..9).map(|_| solver.var()).collect()).collect();
+        for pigeon in &holes {
+            solver.add(pigeon);
+        }
+        for (i, first) in holes.iter().enumerate() {
+            for second in &holes[i + 1..] {
+                for (&a, &b) in first.iter().zip(second) {
+                    solver.add2(!a, !b);
+                }
+            }
+        }
+        assert_eq!(solver.solve_until(|| true), Outcome::Unknown);
+    }
+}
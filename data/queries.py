@@ -248,6 +248,20 @@ class DB:
     def make_department(self, department: str) -> None:
         self.db.execute('INSERT INTO departments VALUES (?)', (department,))
 
+    @rollback_on_exception
+    def make_room_capacity_rule(self, department: str, too_small_priority: Optional[int],
+            wasted_seats_priority: Optional[int] = None, wasted_seats_percent: Optional[int] = None) -> None:
+        self.db.execute('INSERT INTO room_capacity_rules VALUES (?, ?, ?, ?)',
+            (department, too_small_priority, wasted_seats_priority, wasted_seats_percent))
+
+    @rollback_on_exception
+    def pin_section(self, section: str, time_slot: str, room: Optional[str]) -> None:
+        self.db.execute('INSERT INTO pinned_sections VALUES (?, ?, ?)', (section, time_slot, room))
+
+    @rollback_on_exception
+    def set_section_enrollment(self, section: str, enrollment: Optional[int]) -> None:
+        self.db.execute('UPDATE sections SET enrollment = ? WHERE section = ?', (enrollment, section))
+
     def _update_availability(self, faculty: str, available: list[TimeInterval]) -> None:
         if len(available) == 0:
             return
@@ -415,7 +429,7 @@ class DB:
 
     @rollback_on_exception
     def make_section_with_no_faculty(self, section: str, *tags: str) -> None:
-        self.db.execute('INSERT INTO sections VALUES (?)', (section, ))
+        self.db.execute('INSERT INTO sections (section) VALUES (?)', (section, ))
         for tag in tags:
             (room_tags,) = self.db.execute('SELECT COUNT(1) FROM room_tags WHERE room_tag = ?', (tag,)).fetchone()
             (time_slot_tags,) = self.db.execute('SELECT COUNT(1) FROM time_slot_tags WHERE time_slot_tag = ?', (tag,)).fetchone()
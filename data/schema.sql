@@ -91,6 +91,25 @@ CREATE TABLE departments (
     department                  TEXT PRIMARY KEY
 ) WITHOUT ROWID;
 
+-- How a department's sections are matched to room sizes. Capacity is only
+-- checked for sections with a known enrollment. A room with fewer seats than
+-- the enrollment is penalized at too_small_priority, or is removed from the
+-- section's allowed rooms when too_small_priority is NULL. When
+-- wasted_seats_priority is set, a room with more than wasted_seats_percent
+-- percent of the enrollment in seats is penalized at that priority.
+CREATE TABLE room_capacity_rules (
+    department                  TEXT PRIMARY KEY,
+    too_small_priority          INTEGER,
+    wasted_seats_priority       INTEGER,
+    wasted_seats_percent        INTEGER,
+
+    CHECK (too_small_priority IS NULL OR too_small_priority >= 1 AND too_small_priority < 26),
+    CHECK (wasted_seats_priority IS NULL OR wasted_seats_priority >= 1 AND wasted_seats_priority < 26),
+    CHECK (wasted_seats_priority IS NULL AND wasted_seats_percent IS NULL OR wasted_seats_priority IS NOT NULL AND wasted_seats_percent > 100),
+
+    FOREIGN KEY (department) REFERENCES departments (department) ON DELETE CASCADE ON UPDATE CASCADE
+) WITHOUT ROWID;
+
 CREATE TABLE programs (
     program                     TEXT PRIMARY KEY,
     department                  TEXT NOT NULL,
@@ -256,9 +275,12 @@ CREATE TABLE coreqs (
 -- COURSE-SECTION string.
 CREATE TABLE sections (
     section                     TEXT PRIMARY KEY,
+    enrollment                  INTEGER,
     course                      TEXT GENERATED ALWAYS AS (SUBSTR(section, 1, INSTR(section, '-') - 1)) VIRTUAL NOT NULL,
     section_number              TEXT GENERATED ALWAYS AS (SUBSTR(section, INSTR(section, '-') + 1)) VIRTUAL NOT NULL,
 
+    CHECK (enrollment IS NULL OR enrollment > 0),
+
     CHECK (LENGTH(course) >= 6),
     CHECK (LENGTH(section_number) >= 2),
     CHECK (course || '-' || section_number = section),
@@ -292,6 +314,19 @@ CREATE TABLE section_time_slot_tags (
     FOREIGN KEY (time_slot_tag) REFERENCES time_slot_tags (time_slot_tag) ON DELETE CASCADE ON UPDATE CASCADE
 ) WITHOUT ROWID;
 
+-- Pinned sections are placed at exactly this time slot and room (NULL for a
+-- section without rooms) and left there while everything else is optimized.
+-- The pin must be one of the section's allowed options.
+CREATE TABLE pinned_sections (
+    section                     TEXT PRIMARY KEY,
+    time_slot                   TEXT NOT NULL,
+    room                        TEXT,
+
+    FOREIGN KEY (section) REFERENCES sections (section) ON DELETE CASCADE ON UPDATE CASCADE,
+    FOREIGN KEY (time_slot) REFERENCES time_slots (time_slot) ON DELETE CASCADE ON UPDATE CASCADE,
+    FOREIGN KEY (room) REFERENCES rooms (room) ON DELETE CASCADE ON UPDATE CASCADE
+) WITHOUT ROWID;
+
 -- Faculty assignment is intentionally independent of faculty affiliation and
 -- course department. The intersection of this table with schedulable sections
 -- determines which faculty Rust loads for selected course departments.
@@ -459,19 +494,56 @@ CREATE TABLE multiple_section_overrides (
 -- Placements and placement penalties are solver output/history, not input
 -- constraints. They live in the same database so generated schedules can be
 -- inspected against the exact input that produced them.
+--
+-- department_scope is the comma-separated list of departments a placement was
+-- solved for, or empty when it covers every department. Rust only loads and
+-- updates placements whose scope matches the departments it was asked for.
+-- background_placement_id names the placement whose other-department sections
+-- were frozen in place while this one was solved.
+-- baseline_placement_id names the published placement a re-solve tried to
+-- stay close to, with each section moved away from it penalized at
+-- baseline_priority.
+-- label is an optional unique name for a placement, and published marks the
+-- official draft; at most one placement per department scope is published.
+-- prune never deletes labeled or published placements.
+-- command, options, seed, version, and wall_ms record how a placement was
+-- produced: the marmot subcommand with its full option set, the random seed
+-- (if any), the marmot version, and how long the run had been going when it
+-- was last saved. parent_placement_id names the placement it was derived from.
 CREATE TABLE placements (
     placement_id                INTEGER PRIMARY KEY,
     score                       TEXT NOT NULL,
     sort_score                  TEXT NOT NULL,
     optimum_score_prefix        TEXT NOT NULL,
     faculty_preference_priority_policy TEXT NOT NULL DEFAULT 'stated',
+    department_scope            TEXT NOT NULL DEFAULT '',
+    background_placement_id     INTEGER,
+    baseline_placement_id       INTEGER,
+    baseline_priority           INTEGER,
     comment                     TEXT NOT NULL,
     created_at                  TEXT NOT NULL,
     modified_at                 TEXT NOT NULL,
-
-    CHECK (faculty_preference_priority_policy IN ('stated', 'entropy-balanced-v1'))
+    label                       TEXT UNIQUE,
+    published                   INTEGER NOT NULL DEFAULT 0,
+    command                     TEXT NOT NULL DEFAULT '',
+    options                     TEXT NOT NULL DEFAULT '',
+    seed                        INTEGER,
+    version                     TEXT NOT NULL DEFAULT '',
+    wall_ms                     INTEGER,
+    parent_placement_id         INTEGER,
+
+    CHECK (faculty_preference_priority_policy IN ('stated', 'entropy-balanced-v1')),
+    CHECK (label IS NULL OR label <> ''),
+    CHECK (published IN (0, 1)),
+    CHECK (baseline_placement_id IS NULL AND baseline_priority IS NULL
+        OR baseline_placement_id IS NOT NULL AND baseline_priority >= 1 AND baseline_priority < 26),
+    FOREIGN KEY (background_placement_id) REFERENCES placements (placement_id),
+    FOREIGN KEY (baseline_placement_id) REFERENCES placements (placement_id),
+    FOREIGN KEY (parent_placement_id) REFERENCES placements (placement_id) ON DELETE SET NULL
 );
 
+CREATE UNIQUE INDEX published_placement ON placements (department_scope) WHERE published;
+
 CREATE TABLE placement_sections (
     placement_id                INTEGER NOT NULL,
     section                     TEXT NOT NULL,
@@ -485,6 +557,24 @@ CREATE TABLE placement_sections (
     FOREIGN KEY (room) REFERENCES rooms (room) ON DELETE CASCADE ON UPDATE CASCADE
 ) WITHOUT ROWID;
 
+-- each placement records a digest of every section's allowed rooms, allowed
+-- time slots, faculty, hard conflicts, and scoring criteria when it was saved,
+-- so loading it after the input has changed can report what is different.
+-- section is deliberately not a foreign key so deleted sections are still
+-- remembered.
+CREATE TABLE placement_fingerprints (
+    placement_id                INTEGER NOT NULL,
+    section                     TEXT NOT NULL,
+    rooms                       TEXT NOT NULL,
+    time_slots                  TEXT NOT NULL,
+    faculty                     TEXT NOT NULL,
+    hard_conflicts              TEXT NOT NULL,
+    criteria                    TEXT NOT NULL,
+
+    PRIMARY KEY (placement_id, section),
+    FOREIGN KEY (placement_id) REFERENCES placements (placement_id) ON DELETE CASCADE ON UPDATE CASCADE
+) WITHOUT ROWID;
+
 CREATE TABLE placement_penalties (
     placement_penalty_id        INTEGER PRIMARY KEY,
     placement_id                INTEGER NOT NULL,
@@ -1000,6 +1090,32 @@ CREATE VIEW rooms_available_to_sections (department, section, room, room_priorit
     NATURAL LEFT OUTER JOIN section_room_preferences
     GROUP BY department, section, room;
 
+-- Expected enrollment for each schedulable section that has one, with the
+-- capacity rules that apply to it. Cross-listed sections share a room, so the
+-- primary section's enrollment is the sum over every section in the listing.
+-- The rules come from the department that owns the primary section's course.
+CREATE VIEW section_enrollments_to_be_scheduled (department, section, enrollment,
+        too_small_priority, wasted_seats_priority, wasted_seats_percent) AS
+    WITH section_enrollments (section, enrollment) AS (
+        SELECT sections_to_be_scheduled.section, SUM(sections.enrollment)
+        FROM sections_to_be_scheduled
+        JOIN sections
+            ON sections.section = sections_to_be_scheduled.secondary_section
+        GROUP BY sections_to_be_scheduled.section
+        HAVING SUM(sections.enrollment) IS NOT NULL
+    )
+
+    SELECT DISTINCT sections_to_be_scheduled.department, sections_to_be_scheduled.section, section_enrollments.enrollment,
+            too_small_priority, wasted_seats_priority, wasted_seats_percent
+    FROM sections_to_be_scheduled
+    NATURAL JOIN section_enrollments
+    JOIN sections AS primary_sections
+        ON primary_sections.section = sections_to_be_scheduled.section
+    JOIN courses
+        ON courses.course = primary_sections.course
+    LEFT OUTER JOIN room_capacity_rules
+        ON room_capacity_rules.department = courses.department;
+
 -- Rooms that a course-owning department uses in solver input. This is a
 -- department-scoped projection of the raw allowed room tags before faculty
 -- room preferences matter; it exists so Rust loads only rooms that can appear
@@ -1,3 +1,4 @@
+use super::interrupt::interrupted;
 use std::collections::{HashMap, HashSet};
 
 pub struct Encoding {
@@ -18,6 +19,25 @@ pub struct Encoding {
 
     // hallpass variables that are allowed to violate constraints, grouped by priority
     pub hallpasses: HashMap<u8, HashSet<i32>>,
+
+    // when tracking is on, each group of hard clauses is switched on by a selector
+    // variable (listed here with the sections involved) so an unsatisfiable set of
+    // hard clauses can be traced back to the input facts behind it
+    pub hard_groups: Option<Vec<(i32, Vec<usize>)>>,
+
+    // the selector of the hard group currently being encoded (if any)
+    guard: Option<i32>,
+
+    // totalizers over the hallpasses at each priority level: how many hallpasses
+    // each one counts, and its outputs (see hallpass_limit)
+    totalizers: HashMap<u8, (usize, Vec<i32>)>,
+
+    // the incremental solver that every query goes to, its variables, how many of
+    // the clauses it has been given, and whether its last query was unsatisfiable
+    solver: cadical::Solver,
+    solver_vars: Vec<cadical::Var>,
+    loaded_clauses: usize,
+    refuted: bool,
 }
 
 impl Encoding {
@@ -29,7 +49,35 @@ impl Encoding {
             section_time_vars: HashMap::new(),
             problems: HashMap::new(),
             hallpasses: HashMap::new(),
+            hard_groups: None,
+            guard: None,
+            totalizers: HashMap::new(),
+            solver: cadical::Solver::new(),
+            solver_vars: Vec::new(),
+            loaded_clauses: 0,
+            refuted: false,
+        }
+    }
+
+    pub fn track_hard_groups(&mut self) {
+        self.hard_groups = Some(Vec::new());
+    }
+
+    // start a group of hard clauses; the description is only built when tracking
+    pub fn begin_hard_group(&mut self, sections: &[usize], problem: impl FnOnce() -> String) {
+        if self.hard_groups.is_none() {
+            return;
+        }
+        let selector = self.new_var();
+        self.problems.insert(selector, (0, problem()));
+        if let Some(groups) = &mut self.hard_groups {
+            groups.push((selector, sections.to_vec()));
         }
+        self.guard = Some(selector);
+    }
+
+    pub fn end_hard_group(&mut self) {
+        self.guard = None;
     }
 
     pub fn new_var(&mut self) -> i32 {
@@ -44,7 +92,11 @@ impl Encoding {
         hallpass
     }
 
-    pub fn add_clause(&mut self, clause: Vec<i32>) {
+    pub fn add_clause(&mut self, mut clause: Vec<i32>) {
+        // a guarded clause only applies when its group's selector is true
+        if let Some(selector) = self.guard {
+            clause.push(-selector);
+        }
         self.clauses.push(clause);
     }
 
@@ -95,6 +147,29 @@ impl Encoding {
         }
     }
 
+    // a literal that, assumed true, allows at most `bound` of the hallpasses at a priority
+    // level to be true (bound must be less than the number of hallpasses)
+    //
+    // The totalizer behind it is built the first time a level needs one, with outputs for
+    // about twice the requested bound, and is only rebuilt (twice as large again) if a later
+    // bound does not fit. A level's search stays within a few multiples of its answer, so
+    // this avoids a full n-output totalizer over every hallpass at the level.
+    pub fn hallpass_limit(&mut self, priority: u8, bound: usize) -> i32 {
+        let mut literals: Vec<i32> = self.hallpasses.get(&priority).into_iter().flatten().copied().collect();
+        let fits = self
+            .totalizers
+            .get(&priority)
+            .is_some_and(|(inputs, outputs)| *inputs == literals.len() && bound < outputs.len());
+        if !fits {
+            let built = self.totalizers.get(&priority).map_or(0, |(_, outputs)| outputs.len());
+            let limit = (bound + 1).max(built).saturating_mul(2).min(literals.len());
+            literals.sort_unstable();
+            let outputs = self.build_bounded_totalizer_tree(&literals, limit);
+            self.totalizers.insert(priority, (literals.len(), outputs));
+        }
+        -self.totalizers[&priority].1[bound]
+    }
+
     // Build a totalizer tree with only the outputs needed to detect the bound.
     fn build_bounded_totalizer_tree(&mut self, input_literals: &[i32], output_limit: usize) -> Vec<i32> {
         let n = input_literals.len();
@@ -160,62 +235,51 @@ impl Encoding {
         current_outputs
     }
 
-    // solve the current CNF encoding using kissat
+    // solve the current CNF encoding
     //
     // Returns:
     // - `Ok(Some(HashSet<i32>))` if the problem is satisfiable, with a set of true variables
-    // - `Ok(None)` if the problem is unsatisfiable
+    // - `Ok(None)` if the problem is unsatisfiable, or if the search was interrupted
     // - `Err(String)` if an error occurs during solving
-    pub fn solve(&self) -> Result<Option<HashSet<i32>>, String> {
-        // Kissat 4's default preprocessing spends about three seconds on each of these dense,
-        // structured CNFs. This optimization loop creates a fresh solver for every bound, while
-        // the plain configuration solves the same instances directly without that repeated cost.
-        let mut solver = kissat::Solver::with_configuration(kissat::Configuration::Plain);
-
-        // Create a mapping from our variable indices to kissat variables
-        let mut var_map = Vec::with_capacity(self.last_var as usize);
+    pub fn solve(&mut self) -> Result<Option<HashSet<i32>>, String> {
+        self.solve_with_assumptions(&[])
+    }
 
-        // Create variables in the kissat solver
-        for _ in 0..self.last_var {
-            var_map.push(solver.var());
+    // solve the current CNF encoding with extra literals assumed true
+    //
+    // The assumptions hold for this query only, so the same encoding can be queried
+    // repeatedly with different bounds. Every query goes to the same CaDiCaL instance:
+    // clauses added since the last query are loaded first, and what the solver learned
+    // from earlier queries carries over. After an unsatisfiable query,
+    // failed_assumptions says which of the assumptions were needed to refute it.
+    pub fn solve_with_assumptions(&mut self, assumptions: &[i32]) -> Result<Option<HashSet<i32>>, String> {
+        // Create solver variables for any new variables in the encoding
+        while self.solver_vars.len() < self.last_var as usize {
+            let var = self.solver.var();
+            self.solver_vars.push(var);
         }
 
-        // Add all clauses to the solver
-        for clause in &self.clauses {
-            let mut kissat_clause = Vec::with_capacity(clause.len());
-
-            for &lit in clause {
-                let var_idx = lit.unsigned_abs() as usize;
-
-                // ensure the variable index is valid
-                if var_idx == 0 || var_idx > var_map.len() {
-                    return Err(format!("Invalid variable index: {}", var_idx));
-                }
-
-                // get the corresponding kissat variable
-                let var = var_map[var_idx - 1];
-
-                // determine if this is a positive or negative literal
-                if lit > 0 {
-                    kissat_clause.push(var); // positive literal
-                } else {
-                    kissat_clause.push(!var); // negative literal
-                }
-            }
+        // Add the clauses that the solver has not seen yet
+        for i in self.loaded_clauses..self.clauses.len() {
+            let clause = self.solver_literals(&self.clauses[i])?;
+            self.solver.add(&clause);
+        }
+        self.loaded_clauses = self.clauses.len();
 
-            // add the clause to the solver
-            solver.add(&kissat_clause);
+        for lit in self.solver_literals(assumptions)? {
+            self.solver.assume(lit);
         }
 
-        // solve the instance
-        match solver.sat() {
-            Some(solution) => {
+        // solve the instance, giving up on an interrupt
+        self.refuted = false;
+        match self.solver.solve_until(interrupted) {
+            cadical::Outcome::Sat => {
                 // problem is satisfiable, extract the true variables
                 let mut true_vars = HashSet::new();
 
                 // Add each true variable to the HashSet
-                for (i, var) in var_map.iter().enumerate() {
-                    if let Some(true) = solution.get(*var) {
+                for (i, var) in self.solver_vars.iter().enumerate() {
+                    if self.solver.value(*var) {
                         // add the variable to the set if it's true
                         true_vars.insert((i + 1) as i32);
                     }
@@ -223,12 +287,60 @@ impl Encoding {
 
                 Ok(Some(true_vars))
             }
-            None => {
+            cadical::Outcome::Unsat => {
                 // unsatisfiable
+                self.refuted = true;
+                Ok(None)
+            }
+            cadical::Outcome::Unknown => {
+                // interrupted: no answer either way, so callers must check
+                // interrupted() before treating this as unsatisfiable
                 Ok(None)
             }
         }
     }
+
+    // the assumptions of the last query that the solver needed to show it unsatisfiable
+    //
+    // Together with the clauses they are unsatisfiable, though not necessarily minimally
+    // so. Empty if the last query was not refuted, including when it was interrupted.
+    pub fn failed_assumptions(&self, assumptions: &[i32]) -> Vec<i32> {
+        if !self.refuted {
+            return Vec::new();
+        }
+        assumptions
+            .iter()
+            .copied()
+            .filter(|&lit| {
+                let var = self.solver_vars[lit.unsigned_abs() as usize - 1];
+                self.solver.failed(if lit > 0 { var } else { !var })
+            })
+            .collect()
+    }
+
+    // map encoding literals to solver literals
+    fn solver_literals(&self, literals: &[i32]) -> Result<Vec<cadical::Var>, String> {
+        let mut solver_clause = Vec::with_capacity(literals.len());
+        for &lit in literals {
+            let var_idx = lit.unsigned_abs() as usize;
+
+            // ensure the variable index is valid
+            if var_idx == 0 || var_idx > self.solver_vars.len() {
+                return Err(format!("Invalid variable index: {}", var_idx));
+            }
+
+            // get the corresponding solver variable
+            let var = self.solver_vars[var_idx - 1];
+
+            // determine if this is a positive or negative literal
+            if lit > 0 {
+                solver_clause.push(var); // positive literal
+            } else {
+                solver_clause.push(!var); // negative literal
+            }
+        }
+        Ok(solver_clause)
+    }
 }
 
 impl Default for Encoding {
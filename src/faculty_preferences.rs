@@ -1,5 +1,5 @@
 use super::error::{Result, err};
-use super::input::{Days, Input, Time};
+use super::input::{Days, Input, Time, messageln};
 use super::score::{Criterion, FacultyPreferenceKind, MAX_PRIORITY, START_LEVEL_FOR_PREFERENCES};
 use super::solver::Schedule;
 use std::cmp::Ordering;
@@ -100,7 +100,7 @@ pub fn rebalance_faculty_preferences(input: &mut Input, show_details: bool) -> R
         print_rebalancing_details(input, &buckets);
     }
 
-    println!(
+    messageln!(
         "balanced {} faculty preference tiers into {} priorities in {}ms",
         buckets.iter().map(|bucket| bucket.impacts.len()).sum::<usize>(),
         buckets.len(),
@@ -110,10 +110,10 @@ pub fn rebalance_faculty_preferences(input: &mut Input, show_details: bool) -> R
 }
 
 fn print_rebalancing_details(input: &Input, buckets: &[ImpactBucket]) {
-    println!("Faculty preference priority redistribution:");
+    messageln!("Faculty preference priority redistribution:");
     for (bucket_index, bucket) in buckets.iter().enumerate() {
         let priority = START_LEVEL_FOR_PREFERENCES + bucket_index as u8;
-        println!("  priority {priority}:");
+        messageln!("  priority {priority}:");
         for impact in &bucket.impacts {
             let entropy =
                 if impact.remaining == 0 { "infinite".to_string() } else { format!("{:.6} bits", entropy(impact)) };
@@ -121,7 +121,7 @@ fn print_rebalancing_details(input: &Input, buckets: &[ImpactBucket]) {
                 let Criterion::OwnedFacultyPreference(preference) = &input.criteria[criterion_index] else {
                     unreachable!("preference tier points to a non-faculty criterion");
                 };
-                println!(
+                messageln!(
                     "    stated {:2}, entropy {:>13}: {}",
                     impact.stated_priority,
                     entropy,
@@ -458,6 +458,10 @@ mod tests {
                 .map(|&time_slot| TimeSlotWithOptionalPriority { time_slot, priority: None })
                 .collect(),
             faculty: vec![0],
+            enrollment: None,
+            pinned: None,
+            baseline: None,
+            background: false,
             hard_conflicts: vec![],
             criteria: vec![],
             neighbors: vec![],
@@ -479,7 +483,10 @@ mod tests {
         let monday = Days::parse("M").unwrap();
         let mut input = Input {
             term_name: "test".to_string(),
-            rooms: vec![Room { name: "A".to_string() }, Room { name: "B".to_string() }],
+            departments: vec![],
+            background_id: None,
+            baseline: None,
+            rooms: vec![Room { name: "A".to_string(), capacity: 30 }, Room { name: "B".to_string(), capacity: 60 }],
             time_slots: (0..3)
                 .map(|hour| TimeSlot {
                     name: format!("T{hour}"),
@@ -0,0 +1,172 @@
+use super::error::Result;
+use super::input::*;
+use super::score::*;
+use sqlite::{Connection, State};
+use std::collections::HashMap;
+
+// each placement records a digest of the input that shaped every section it
+// covers, so a placement loaded after the input data has been edited can say
+// exactly which sections changed and how
+
+const ASPECTS: [&str; 5] = ["allowed rooms", "allowed time slots", "faculty", "hard conflicts", "scoring criteria"];
+
+// how a section's input differs from when a placement was saved
+pub enum InputChange {
+    Removed(String),
+    Added(usize),
+    Changed(usize, Vec<&'static str>),
+}
+
+impl InputChange {
+    pub fn describe(&self, input: &Input) -> String {
+        match self {
+            InputChange::Removed(name) => format!("{} is no longer in the input", name),
+            InputChange::Added(section) => format!("{} is new", input.sections[*section].name),
+            InputChange::Changed(section, aspects) => {
+                format!("{} has changed {}", input.sections[*section].name, aspects.join(", "))
+            }
+        }
+    }
+}
+
+// digests of a section's allowed rooms, allowed time slots, faculty, hard
+// conflicts, and scoring criteria, in the order of ASPECTS; everything is
+// described by name so the digests do not depend on how the input was indexed
+pub fn fingerprint_section(input: &Input, section: usize) -> [String; 5] {
+    let elt = &input.sections[section];
+    let rooms = elt
+        .rooms
+        .iter()
+        .map(|room| format!("{}:{}", input.rooms[room.room].name, room.priority.map_or(0, |p| p as i64 + 1)))
+        .collect();
+    let time_slots = elt
+        .time_slots
+        .iter()
+        .map(|ts| format!("{}:{}", input.time_slots[ts.time_slot].name, ts.priority.map_or(0, |p| p as i64 + 1)))
+        .collect();
+    let faculty = elt.faculty.iter().map(|&faculty| input.faculty[faculty].name.clone()).collect();
+    let hard_conflicts = elt.hard_conflicts.iter().map(|&other| input.sections[other].name.clone()).collect();
+    let criteria = elt
+        .criteria
+        .iter()
+        .map(|&criterion| match &input.criteria[criterion] {
+            // balancing re-tiers every faculty member's preferences when any
+            // one changes, so only the stated priority belongs to this section
+            Criterion::OwnedFacultyPreference(preference) => {
+                format!("{}: {}", preference.stated_priority, preference.description(input))
+            }
+            criterion => criterion.debug(input),
+        })
+        .collect();
+    [rooms, time_slots, faculty, hard_conflicts, criteria].map(digest)
+}
+
+// 64-bit FNV-1a over the sorted lines, which is stable across builds
+fn digest(mut lines: Vec<String>) -> String {
+    lines.sort_unstable();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for line in &lines {
+        for &byte in line.as_bytes().iter().chain(b"\n") {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+// record the fingerprint of every non-background section for a placement,
+// replacing any previous record; the caller owns the transaction
+pub fn save_fingerprints(db: &Connection, placement_id: i64, input: &Input) -> Result<()> {
+    let mut stmt = db.prepare("DELETE FROM placement_fingerprints WHERE placement_id = ?")?;
+    stmt.bind((1, placement_id))?;
+    while stmt.next()? != State::Done {
+        // no return rows expected
+    }
+
+    for (section, elt) in input.sections.iter().enumerate() {
+        if elt.background {
+            continue;
+        }
+        let [rooms, time_slots, faculty, hard_conflicts, criteria] = fingerprint_section(input, section);
+        let mut stmt = db.prepare(
+            "INSERT INTO placement_fingerprints
+                (placement_id, section, rooms, time_slots, faculty, hard_conflicts, criteria)
+            VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )?;
+        stmt.bind((1, placement_id))?;
+        stmt.bind((2, elt.name.as_str()))?;
+        stmt.bind((3, rooms.as_str()))?;
+        stmt.bind((4, time_slots.as_str()))?;
+        stmt.bind((5, faculty.as_str()))?;
+        stmt.bind((6, hard_conflicts.as_str()))?;
+        stmt.bind((7, criteria.as_str()))?;
+        while stmt.next()? != State::Done {
+            // no return rows expected
+        }
+    }
+    Ok(())
+}
+
+// compare the current input against the fingerprint saved with a placement;
+// None if the placement predates fingerprints
+pub fn compare_fingerprints(db: &Connection, placement_id: i64, input: &Input) -> Result<Option<Vec<InputChange>>> {
+    let mut stmt = db.prepare(
+        "SELECT section, rooms, time_slots, faculty, hard_conflicts, criteria
+            FROM placement_fingerprints
+            WHERE placement_id = ?
+            ORDER BY section",
+    )?;
+    stmt.bind((1, placement_id))?;
+    let mut saved = HashMap::new();
+    while stmt.next()? == State::Row {
+        let section: String = stmt.read(0)?;
+        let mut digests: [String; 5] = Default::default();
+        for (i, elt) in digests.iter_mut().enumerate() {
+            *elt = stmt.read(i + 1)?;
+        }
+        saved.insert(section, digests);
+    }
+    if saved.is_empty() {
+        return Ok(None);
+    }
+
+    let mut changes = Vec::new();
+    let mut names: Vec<&String> = saved.keys().collect();
+    names.sort_unstable();
+    for name in names {
+        if !input.sections.iter().any(|elt| !elt.background && &elt.name == name) {
+            changes.push(InputChange::Removed(name.clone()));
+        }
+    }
+    for (section, elt) in input.sections.iter().enumerate() {
+        if elt.background {
+            continue;
+        }
+        let Some(old) = saved.get(&elt.name) else {
+            changes.push(InputChange::Added(section));
+            continue;
+        };
+        let new = fingerprint_section(input, section);
+        let aspects: Vec<&'static str> =
+            ASPECTS.iter().zip(old.iter().zip(&new)).filter(|(_, (a, b))| a != b).map(|(&aspect, _)| aspect).collect();
+        if !aspects.is_empty() {
+            changes.push(InputChange::Changed(section, aspects));
+        }
+    }
+    Ok(Some(changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_ignore_order_but_not_content() {
+        let a = digest(vec!["Smith 107:0".to_string(), "Smith 108:3".to_string()]);
+        let b = digest(vec!["Smith 108:3".to_string(), "Smith 107:0".to_string()]);
+        let c = digest(vec!["Smith 108:4".to_string(), "Smith 107:0".to_string()]);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(digest(Vec::new()), "cbf29ce484222325");
+    }
+}
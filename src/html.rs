@@ -0,0 +1,122 @@
+use super::error::{Result, err};
+use super::input::*;
+use super::json::*;
+use super::score::*;
+use super::solver::*;
+use std::collections::HashMap;
+
+// the browser viewer, with its placement data script replaced by the schedule being reported
+const VIEWER: &str = include_str!("../ui/index.html");
+const DATA_SCRIPT: &str = r#"<script src="placement.js" defer></script>"#;
+
+// write a standalone HTML report of a schedule: room and instructor
+// timetables plus the problems list, all in one file
+pub fn write_html(
+    input: &Input,
+    schedule: &Schedule,
+    cross_listings: &HashMap<String, usize>,
+    title: &str,
+    path: &str,
+) -> Result<()> {
+    if !VIEWER.contains(DATA_SCRIPT) {
+        return err("the viewer page no longer loads placement.js");
+    }
+    let data = format!("<script>\nwindow.placement = {};\n</script>", placement_json(input, schedule, cross_listings));
+    let page = VIEWER.replacen(DATA_SCRIPT, &data, 1).replacen(
+        "<title>Marmot viewer</title>",
+        &format!("<title>{}</title>", escape_html(title)),
+        1,
+    );
+    if let Err(e) = std::fs::write(path, page) {
+        return err(format!("unable to write {}: {}", path, e));
+    }
+    Ok(())
+}
+
+// write the data script that ui/index.html loads as placement.js, so the
+// viewer can be opened next to it instead of as a standalone report
+pub fn write_placement_data(
+    input: &Input,
+    schedule: &Schedule,
+    cross_listings: &HashMap<String, usize>,
+    path: &str,
+) -> Result<()> {
+    let data = format!("window.placement = {};\n", placement_json(input, schedule, cross_listings));
+    if let Err(e) = std::fs::write(path, data) {
+        return err(format!("unable to write {}: {}", path, e));
+    }
+    Ok(())
+}
+
+// the viewer's data format: one object per section, with cross-listed
+// secondaries folded in as extra names and each penalty listed under every
+// section it involves
+fn placement_json(input: &Input, schedule: &Schedule, cross_listings: &HashMap<String, usize>) -> Json {
+    let mut names: Vec<Vec<&str>> = input.sections.iter().map(|elt| vec![elt.name.as_str()]).collect();
+    let mut secondaries: Vec<(&String, &usize)> = cross_listings.iter().collect();
+    secondaries.sort_unstable();
+    for (name, &section) in secondaries {
+        names[section].push(name);
+    }
+
+    let mut problems: Vec<Vec<(u8, String)>> = vec![Vec::new(); input.sections.len()];
+    for (section, placement) in schedule.placements.iter().enumerate() {
+        if placement.time_slot.is_none() {
+            problems[section]
+                .push((LEVEL_FOR_UNPLACED_SECTION, format!("{} is not placed", input.sections[section].name)));
+        }
+    }
+    for penalty_list in &schedule.penalties {
+        for penalty in penalty_list {
+            let (priority, msg) = penalty.get_score_message(input, schedule);
+            for section in penalty.get_sections(input) {
+                problems[section].push((priority, msg.clone()));
+            }
+        }
+    }
+
+    let mut list = Vec::new();
+    for (section, elt) in input.sections.iter().enumerate() {
+        if elt.background {
+            continue;
+        }
+        let placement = &schedule.placements[section];
+
+        // the viewer lays sections out by room, so roomless sections are listed but not drawn
+        let is_placed = placement.time_slot.is_some() && placement.room.is_some();
+        let mut prefixes: Vec<&str> =
+            names[section].iter().map(|name| name.split_once(' ').map_or(*name, |(prefix, _)| prefix)).collect();
+        prefixes.dedup();
+        let instructors: Vec<&str> = elt.faculty.iter().map(|&faculty| input.faculty[faculty].name.as_str()).collect();
+        let problems = problems[section]
+            .iter()
+            .map(|(priority, msg)| Json::Object(vec![("score", (*priority).into()), ("message", msg.as_str().into())]))
+            .collect();
+
+        list.push(Json::Object(vec![
+            ("names", names[section].clone().into()),
+            ("prefixes", prefixes.into()),
+            ("instructors", instructors.into()),
+            ("is_placed", is_placed.into()),
+            ("room", placement.room.map(|room| input.rooms[room].name.as_str()).into()),
+            ("time_slot", placement.time_slot.map(|time_slot| input.time_slots[time_slot].name.as_str()).into()),
+            ("problems", Json::Array(problems)),
+        ]));
+    }
+    Json::Array(list)
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_strings_cannot_close_the_script_element() {
+        assert_eq!(Json::quote("a \"b\"\n</script>"), r#""a \"b\"\n\u003c/script>""#);
+        assert!(VIEWER.contains(DATA_SCRIPT));
+    }
+}
@@ -0,0 +1,249 @@
+use super::error::{Result, err};
+use super::input::*;
+use super::solver::*;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const WEEKDAYS: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+// a calendar date, stored as days since 1970-01-01
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Date {
+    days: i64,
+}
+
+impl Date {
+    // "2026-08-19"
+    fn parse(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.trim().split('-').collect();
+        let [year, month, day] = parts.as_slice() else {
+            return err(format!("unable to parse date {}", s));
+        };
+        match (year.parse::<i64>(), month.parse::<i64>(), day.parse::<i64>()) {
+            (Ok(year), Ok(month @ 1..=12), Ok(day @ 1..=31)) => Ok(Date::from_civil(year, month, day)),
+            _ => err(format!("unable to parse date {}", s)),
+        }
+    }
+
+    // days since the epoch for a proleptic Gregorian date
+    fn from_civil(year: i64, month: i64, day: i64) -> Self {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        Date { days: era * 146097 + day_of_era - 719468 }
+    }
+
+    fn to_civil(self) -> (i64, i64, i64) {
+        let days = self.days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        (year, month, day)
+    }
+
+    // 0 for Monday through 6 for Sunday, matching the bit order of Days
+    fn weekday(self) -> usize {
+        (self.days + 3).rem_euclid(7) as usize
+    }
+
+    // local date-time in iCalendar form, e.g., 20260819T090000
+    fn at(self, minutes: u16) -> String {
+        let (year, month, day) = self.to_civil();
+        format!("{:04}{:02}{:02}T{:02}{:02}00", year, month, day, minutes / 60, minutes % 60)
+    }
+}
+
+// the term a schedule will run over, with the dates when no classes meet
+pub struct TermCalendar {
+    start: Date,
+    end: Date,
+    holidays: Vec<Date>,
+}
+
+impl TermCalendar {
+    pub fn new(start: &str, end: &str, holidays: &[String]) -> Result<Self> {
+        let start = Date::parse(start)?;
+        let end = Date::parse(end)?;
+        if end < start {
+            return err("term ends before it starts");
+        }
+        let holidays = holidays.iter().map(|elt| Date::parse(elt)).collect::<Result<Vec<_>>>()?;
+        Ok(TermCalendar { start, end, holidays })
+    }
+}
+
+// write one calendar per faculty member and one per room into a directory,
+// each with a weekly recurring event for every placed section
+pub fn write_ical(
+    input: &Input,
+    schedule: &Schedule,
+    term: &TermCalendar,
+    cross_listings: &HashMap<String, usize>,
+    dir: &str,
+) -> Result<()> {
+    let mut names: Vec<Vec<&str>> = input.sections.iter().map(|elt| vec![elt.name.as_str()]).collect();
+    let mut secondaries: Vec<(&String, &usize)> = cross_listings.iter().collect();
+    secondaries.sort_unstable();
+    for (name, &section) in secondaries {
+        names[section].push(name);
+    }
+
+    let stamp = utc_stamp();
+    let mut events = HashMap::new();
+    let mut by_faculty = vec![Vec::new(); input.faculty.len()];
+    let mut by_room = vec![Vec::new(); input.rooms.len()];
+    for (section, elt) in input.sections.iter().enumerate() {
+        let placement = &schedule.placements[section];
+        let Some(time_slot) = placement.time_slot else {
+            continue;
+        };
+        if elt.background {
+            continue;
+        }
+        let Some(event) = section_event(input, term, &names[section], time_slot, placement.room, &elt.faculty, &stamp)
+        else {
+            println!("{} never meets during the term", elt.name);
+            continue;
+        };
+        events.insert(section, event);
+        for &faculty in &elt.faculty {
+            by_faculty[faculty].push(section);
+        }
+        if let Some(room) = placement.room {
+            by_room[room].push(section);
+        }
+    }
+
+    for (kind, owners, lists) in [
+        ("faculty", input.faculty.iter().map(|elt| elt.name.as_str()).collect::<Vec<_>>(), &by_faculty),
+        ("rooms", input.rooms.iter().map(|elt| elt.name.as_str()).collect::<Vec<_>>(), &by_room),
+    ] {
+        let subdir = format!("{}/{}", dir, kind);
+        if let Err(e) = std::fs::create_dir_all(&subdir) {
+            return err(format!("unable to create {}: {}", subdir, e));
+        }
+        let mut count = 0;
+        for (owner, sections) in owners.iter().zip(lists) {
+            if sections.is_empty() {
+                continue;
+            }
+            let mut out = String::new();
+            push_line(&mut out, "BEGIN:VCALENDAR");
+            push_line(&mut out, "VERSION:2.0");
+            push_line(&mut out, "PRODID:-//marmot//timetable//EN");
+            push_line(&mut out, "CALSCALE:GREGORIAN");
+            push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(&format!("{}: {}", input.term_name, owner))));
+            for section in sections {
+                out.push_str(&events[section]);
+            }
+            push_line(&mut out, "END:VCALENDAR");
+
+            let path = format!("{}/{}.ics", subdir, file_name(owner));
+            if let Err(e) = std::fs::write(&path, out) {
+                return err(format!("unable to write {}: {}", path, e));
+            }
+            count += 1;
+        }
+        println!("wrote {} calendars to {}", count, subdir);
+    }
+    Ok(())
+}
+
+// a VEVENT repeating weekly from the first meeting in the term through its
+// last day, skipping holidays; None if the section never meets in the term
+fn section_event(
+    input: &Input,
+    term: &TermCalendar,
+    names: &[&str],
+    time_slot: usize,
+    room: Option<usize>,
+    faculty: &[usize],
+    stamp: &str,
+) -> Option<String> {
+    let time_slot = &input.time_slots[time_slot];
+    let meets = |date: Date| time_slot.days.days & (1 << date.weekday()) != 0;
+    let first = (term.start.days..=term.end.days).map(|days| Date { days }).find(|&date| meets(date))?;
+    let start = time_slot.start_time.minutes;
+    let end = start + time_slot.duration.minutes;
+    let by_day: Vec<&str> =
+        (0..7).filter(|day| time_slot.days.days & (1 << day) != 0).map(|day| WEEKDAYS[day]).collect();
+
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VEVENT");
+    push_line(&mut out, &format!("UID:{}-{}@marmot", file_name(names[0]), term.start.at(0)));
+    push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+    push_line(&mut out, &format!("DTSTART:{}", first.at(start)));
+    push_line(&mut out, &format!("DTEND:{}", first.at(end)));
+    push_line(&mut out, &format!("RRULE:FREQ=WEEKLY;BYDAY={};UNTIL={}", by_day.join(","), term.end.at(23 * 60 + 59)));
+    for &holiday in &term.holidays {
+        if holiday >= first && holiday <= term.end && meets(holiday) {
+            push_line(&mut out, &format!("EXDATE:{}", holiday.at(start)));
+        }
+    }
+    push_line(&mut out, &format!("SUMMARY:{}", escape_text(&names.join(" / "))));
+    if let Some(room) = room {
+        push_line(&mut out, &format!("LOCATION:{}", escape_text(&input.rooms[room].name)));
+    }
+    if !faculty.is_empty() {
+        let faculty: Vec<&str> = faculty.iter().map(|&elt| input.faculty[elt].name.as_str()).collect();
+        push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(&faculty.join(", "))));
+    }
+    push_line(&mut out, "END:VEVENT");
+    Some(out)
+}
+
+// content lines end in CRLF and are folded after 75 octets
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for ch in line.chars() {
+        if width + ch.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += ch.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+// "Smith 107" => "Smith-107"
+fn file_name(s: &str) -> String {
+    s.chars().map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '-' }).collect()
+}
+
+fn utc_stamp() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elt| elt.as_secs() as i64);
+    let (year, month, day) = Date { days: seconds.div_euclid(86400) }.to_civil();
+    let time = seconds.rem_euclid(86400);
+    format!("{:04}{:02}{:02}T{:02}{:02}{:02}Z", year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_round_trip_and_know_their_weekdays() {
+        let date = Date::parse("2026-08-19").unwrap();
+        assert_eq!(date.to_civil(), (2026, 8, 19));
+        assert_eq!(WEEKDAYS[date.weekday()], "WE");
+        assert_eq!(Date::parse("2024-02-29").unwrap().to_civil(), (2024, 2, 29));
+        assert_eq!(Date::parse("1970-01-01").unwrap().days, 0);
+        assert_eq!(date.at(9 * 60 + 30), "20260819T093000");
+
+        let mut out = String::new();
+        push_line(&mut out, &"x".repeat(80));
+        assert_eq!(out, format!("{}\r\n {}\r\n", "x".repeat(75), "x".repeat(5)));
+    }
+}
@@ -1,7 +1,9 @@
 #![allow(clippy::collapsible_if)]
 
+use super::TweakSpec;
 use super::error::{Result, err};
 use super::faculty_preferences::{FacultyPreferencePriorityPolicy, rebalance_faculty_preferences};
+use super::fingerprint::*;
 use super::score::*;
 use super::solver::*;
 use sqlite::{Connection, OpenFlags, State, Value};
@@ -10,8 +12,44 @@ use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Write;
 use std::ops;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 
+// the messages printed while loading the input and a saved schedule go to
+// stdout with the rest of the output, except for commands that write JSON
+// there, which send them to stderr instead
+static MESSAGES_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+pub fn send_messages_to_stderr() {
+    MESSAGES_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+pub fn messages_to_stderr() -> bool {
+    MESSAGES_TO_STDERR.load(Ordering::Relaxed)
+}
+
+macro_rules! message {
+    ($($arg:tt)*) => {
+        if $crate::input::messages_to_stderr() {
+            eprint!($($arg)*);
+        } else {
+            print!($($arg)*);
+        }
+    };
+}
+
+macro_rules! messageln {
+    ($($arg:tt)*) => {
+        if $crate::input::messages_to_stderr() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use messageln;
+
 //
 //
 // Input data
@@ -25,6 +63,16 @@ pub struct Input {
     // the name of the term
     pub term_name: String,
 
+    // the departments being scheduled (empty for all departments)
+    pub departments: Vec<String>,
+
+    // the placement other departments' sections are frozen from (if any)
+    pub background_id: Option<i64>,
+
+    // the published placement a re-solve should stay close to (if any),
+    // and the priority of the penalty for each section that moves from it
+    pub baseline: Option<(i64, u8)>,
+
     // core schedule data
     pub rooms: Vec<Room>,
     pub time_slots: Vec<TimeSlot>,
@@ -40,6 +88,7 @@ pub struct Input {
 #[derive(Clone)]
 pub struct Room {
     pub name: String,
+    pub capacity: usize,
 }
 
 #[derive(Clone)]
@@ -68,6 +117,20 @@ pub struct Section {
     // faculty (if any) assigned to this section
     pub faculty: Vec<usize>,
 
+    // expected enrollment (if known), including any cross-listed sections
+    pub enrollment: Option<usize>,
+
+    // a pinned section is placed at this time slot and room when the
+    // schedule is created, and is never moved or displaced after that
+    pub pinned: Option<(usize, Option<usize>)>,
+
+    // where the baseline placement put this section (if anywhere)
+    pub baseline: Option<(usize, Option<usize>)>,
+
+    // a background section belongs to another department's frozen placement
+    // and is only here to occupy its room and time
+    pub background: bool,
+
     // hard conflicts
     pub hard_conflicts: Vec<usize>,
 
@@ -78,6 +141,22 @@ pub struct Section {
     pub neighbors: Vec<usize>,
 }
 
+impl Section {
+    pub fn is_pinned(&self) -> bool {
+        self.pinned.is_some()
+    }
+}
+
+impl Input {
+    // e.g., "Smith 108 at MWF0900+50", or just the time slot for no room
+    pub fn describe_placement(&self, time_slot: usize, room: Option<usize>) -> String {
+        match room {
+            Some(room) => format!("{} at {}", self.rooms[room].name, self.time_slots[time_slot].name),
+            None => self.time_slots[time_slot].name.clone(),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq)]
 pub struct RoomWithOptionalPriority {
     pub room: usize,
@@ -312,6 +391,9 @@ impl Faculty {
 pub fn load_input(
     db_path: &str,
     departments: &[String],
+    background_id: Option<i64>,
+    baseline: Option<(i64, usize)>,
+    pins: &[TweakSpec],
     faculty_preference_priority_policy: FacultyPreferencePriorityPolicy,
     show_faculty_preference_priorities: bool,
 ) -> Result<Input> {
@@ -320,7 +402,7 @@ pub fn load_input(
     {
         return err("--show-faculty-preference-priorities requires faculty preference balancing");
     }
-    print!("loading input data");
+    message!("loading input data");
     let start = Instant::now();
 
     let db = Connection::open_with_flags(db_path, OpenFlags::new().with_read_only().with_no_mutex())?;
@@ -334,12 +416,41 @@ pub fn load_input(
         term_name = stmt.read(0)?;
     }
 
+    // a misspelled department would otherwise quietly load nothing
+    let mut departments = departments.to_vec();
+    departments.sort_unstable();
+    departments.dedup();
+    for department in &departments {
+        let mut stmt = db.prepare("SELECT department FROM departments WHERE department = ?")?;
+        stmt.bind((1, department.as_str()))?;
+        if stmt.next()? != State::Row {
+            return err(format!("unknown department {}", department));
+        }
+    }
+    let departments = departments.as_slice();
+    let baseline = match baseline {
+        Some((id, priority)) if (1..=MAX_PRIORITY as usize).contains(&priority) => Some((id, priority as u8)),
+        Some(_) => return err(format!("baseline priority must be between 1 and {}", MAX_PRIORITY)),
+        None => None,
+    };
+
     let (rooms, room_index) = load_rooms(&db, departments)?;
-    let (time_slots, time_slot_index) = load_time_slots(&db, departments)?;
-    let time_slot_conflicts = load_time_slot_conflicts(&db, &time_slot_index, departments)?;
+    let (mut time_slots, mut time_slot_index) = load_time_slots(&db, departments)?;
+    let mut time_slot_conflicts = load_time_slot_conflicts(&db, &time_slot_index, departments)?;
+    let background = match background_id {
+        Some(id) => load_background(&db, id, departments, &room_index, &mut time_slots, &mut time_slot_index)?,
+        None => Vec::new(),
+    };
+    extend_time_slot_conflicts(&mut time_slot_conflicts, &time_slots);
     let (mut faculty, faculty_index) = load_faculty(&db, departments)?;
-    let (mut sections, section_index, mut criteria) = load_sections(&db, &room_index, &time_slot_index, departments)?;
+    let (mut sections, mut section_index, mut criteria) =
+        load_sections(&db, &room_index, &time_slot_index, departments)?;
+    load_room_capacities(&db, &rooms, &mut sections, &section_index, &mut criteria, departments)?;
     load_conflicts(&db, &mut sections, &section_index, &mut criteria, departments)?;
+    if !background.is_empty() {
+        add_background_sections(&mut sections, &mut section_index, background);
+        load_background_conflicts(&db, &mut sections, &section_index, &mut criteria, departments)?;
+    }
     load_anti_conflicts(&db, &sections, &section_index, &mut criteria, departments)?;
     load_time_pattern_matches(&db, &faculty_index, &section_index, &mut criteria, departments)?;
     load_faculty_section_assignments(
@@ -357,6 +468,7 @@ pub fn load_input(
             &db,
             &faculty,
             &faculty_index,
+            &sections,
             &section_index,
             &room_index,
             &time_slot_index,
@@ -367,11 +479,33 @@ pub fn load_input(
         load_collapsed_room_time_preferences(&sections, &mut criteria);
     }
 
+    load_pins(&db, &mut sections, &section_index, &time_slot_index, &room_index, pins, departments)?;
+    check_pins(&sections, &time_slot_conflicts)?;
+    let warnings = match baseline {
+        Some((id, priority)) => load_baseline(
+            &db,
+            &mut sections,
+            &section_index,
+            &time_slot_index,
+            &room_index,
+            &mut criteria,
+            id,
+            priority,
+        )?,
+        None => Vec::new(),
+    };
+
     compute_neighbors(&mut sections, &criteria);
-    println!(" took {}ms", start.elapsed().as_millis());
+    messageln!(" took {}ms", start.elapsed().as_millis());
+    for warning in warnings {
+        messageln!("{}", warning);
+    }
 
     let mut input = Input {
         term_name,
+        departments: departments.to_vec(),
+        background_id,
+        baseline,
         rooms,
         time_slots,
         faculty,
@@ -481,6 +615,7 @@ fn load_owned_room_time_preferences(
     db: &Connection,
     faculty_list: &[Faculty],
     faculty_index: &HashMap<String, usize>,
+    sections: &[Section],
     section_index: &HashMap<String, usize>,
     room_index: &HashMap<String, usize>,
     time_slot_index: &HashMap<String, usize>,
@@ -504,6 +639,11 @@ fn load_owned_room_time_preferences(
         let faculty = *faculty_index.get(&faculty_name).ok_or(format!("unknown faculty {faculty_name}"))?;
         let section = *section_index.get(&section_name).ok_or(format!("unknown section {section_name}"))?;
         let room = *room_index.get(&room_name).ok_or(format!("unknown room {room_name}"))?;
+
+        // rooms that are too small may have been removed from the section
+        if !sections[section].rooms.iter().any(|elt| elt.room == room) {
+            continue;
+        }
         if !matches!(&current, Some((f, s, p, _)) if *f == faculty && *s == section && *p == priority) {
             if let Some((old_faculty, old_section, old_priority, rooms)) = current.take() {
                 criteria.push(Criterion::OwnedFacultyPreference(FacultyPreference {
@@ -576,7 +716,7 @@ pub fn load_rooms(db: &Connection, departments: &[String]) -> Result<(Vec<Room>,
     let dept_in = dept_clause(departments, &["department".into()], true);
     let mut stmt = db.prepare(format!(
         "
-            SELECT DISTINCT room
+            SELECT DISTINCT room, capacity
             FROM rooms_used_by_departments
             {}
             ORDER BY building, CAST (room_number AS INTEGER), room_number",
@@ -587,7 +727,8 @@ pub fn load_rooms(db: &Connection, departments: &[String]) -> Result<(Vec<Room>,
     let mut rooms = Vec::new();
     let mut room_index = HashMap::new();
     while stmt.next()? == State::Row {
-        let room = Room { name: stmt.read(0)? };
+        let capacity: i64 = stmt.read(1)?;
+        let room = Room { name: stmt.read(0)?, capacity: capacity as usize };
         room_index.insert(room.name.clone(), rooms.len());
         rooms.push(room);
     }
@@ -595,6 +736,93 @@ pub fn load_rooms(db: &Connection, departments: &[String]) -> Result<(Vec<Room>,
     Ok((rooms, room_index))
 }
 
+// load the placements of every out-of-scope section in a frozen background placement,
+// adding any time slots they use that are not already loaded
+fn load_background(
+    db: &Connection,
+    background_id: i64,
+    departments: &[String],
+    room_index: &HashMap<String, usize>,
+    time_slots: &mut Vec<TimeSlot>,
+    time_slot_index: &mut HashMap<String, usize>,
+) -> Result<Vec<(String, usize, Option<usize>)>> {
+    if departments.is_empty() {
+        return err("a background placement can only be used when scheduling specific departments");
+    }
+
+    let mut stmt = db.prepare("SELECT placement_id FROM placements WHERE placement_id = ?")?;
+    stmt.bind((1, background_id))?;
+    if stmt.next()? != State::Row {
+        return err(format!("background placement {} not found", background_id));
+    }
+
+    let dept_in = dept_clause(departments, &["department".into()], true);
+    let mut stmt = db.prepare(format!(
+        "
+            SELECT section, time_slot, room, days, start_time, duration
+            FROM placement_sections
+            NATURAL JOIN time_slots
+            WHERE placement_id = ?
+              AND section NOT IN (SELECT section FROM sections_to_be_scheduled {})
+            ORDER BY section",
+        dept_in
+    ))?;
+    stmt.bind((1, background_id))?;
+    for (i, value) in as_values(departments) {
+        stmt.bind((i + 1, value))?;
+    }
+
+    let mut background = Vec::new();
+    while stmt.next()? == State::Row {
+        let section_name: String = stmt.read(0)?;
+        let time_slot_name: String = stmt.read(1)?;
+        let room_name: Option<String> = stmt.read(2)?;
+
+        // a room none of our sections can use does not block anything
+        let room = room_name.and_then(|name| room_index.get(&name).copied());
+
+        let time_slot = match time_slot_index.get(&time_slot_name) {
+            Some(&time_slot) => time_slot,
+            None => {
+                let days: String = stmt.read(3)?;
+                let start_time: i64 = stmt.read(4)?;
+                let duration: i64 = stmt.read(5)?;
+                time_slot_index.insert(time_slot_name.clone(), time_slots.len());
+                time_slots.push(TimeSlot {
+                    name: time_slot_name,
+                    days: Days::parse(&days)?,
+                    start_time: Time::new(start_time as u16),
+                    duration: Duration::new(duration as u16),
+                });
+                time_slots.len() - 1
+            }
+        };
+        background.push((section_name, time_slot, room));
+    }
+
+    Ok(background)
+}
+
+// grow the time slot conflict matrix to cover time slots added after it was loaded
+fn extend_time_slot_conflicts(conflicts: &mut Vec<Vec<bool>>, time_slots: &[TimeSlot]) {
+    let old_len = conflicts.len();
+    let new_len = time_slots.len();
+    for row in conflicts.iter_mut() {
+        row.resize(new_len, false);
+    }
+    conflicts.resize(new_len, vec![false; new_len]);
+    for a in old_len..new_len {
+        for b in 0..new_len {
+            let (x, y) = (&time_slots[a], &time_slots[b]);
+            let overlap = !x.days.intersect(&y.days).is_empty()
+                && x.start_time < y.start_time + y.duration
+                && y.start_time < x.start_time + x.duration;
+            conflicts[a][b] = overlap;
+            conflicts[b][a] = overlap;
+        }
+    }
+}
+
 // load all time slots
 pub fn load_time_slots(db: &Connection, departments: &[String]) -> Result<(Vec<TimeSlot>, HashMap<String, usize>)> {
     let dept_in = dept_clause(departments, &["department".into()], true);
@@ -732,6 +960,10 @@ pub fn load_sections(
                     rooms: Vec::new(),
                     time_slots: Vec::new(),
                     faculty: Vec::new(),
+                    enrollment: None,
+                    pinned: None,
+                    baseline: None,
+                    background: false,
                     hard_conflicts: Vec::new(),
                     criteria: Vec::new(),
                     neighbors: Vec::new(),
@@ -794,6 +1026,74 @@ pub fn load_sections(
     Ok((sections, section_index, criteria))
 }
 
+// load expected enrollments and turn room capacity rules into criteria
+pub fn load_room_capacities(
+    db: &Connection,
+    rooms: &[Room],
+    sections: &mut [Section],
+    section_index: &HashMap<String, usize>,
+    criteria: &mut Vec<Criterion>,
+    departments: &[String],
+) -> Result<()> {
+    let dept_in = dept_clause(departments, &["department".into()], true);
+    let mut stmt = db.prepare(format!(
+        "
+            SELECT DISTINCT section, enrollment, too_small_priority, wasted_seats_priority, wasted_seats_percent
+            FROM section_enrollments_to_be_scheduled
+            {}
+            ORDER BY section",
+        dept_in
+    ))?;
+    stmt.bind_iter(as_values(departments))?;
+
+    while stmt.next()? == State::Row {
+        let section_name: String = stmt.read(0)?;
+        let enrollment = stmt.read::<i64, _>(1)? as usize;
+        let too_small_priority: Option<i64> = stmt.read(2)?;
+        let wasted_seats_priority: Option<i64> = stmt.read(3)?;
+        let wasted_seats_percent: Option<i64> = stmt.read(4)?;
+
+        let Some(&index) = section_index.get(&section_name) else {
+            return err(format!("enrollment found for section {} but section not found", section_name));
+        };
+        let section = &mut sections[index];
+        section.enrollment = Some(enrollment);
+
+        // a section with no rooms does not need a room of any size
+        if section.rooms.is_empty() {
+            continue;
+        }
+
+        // without a priority, rooms that are too small are not allowed at all
+        let too_small = |room: &usize| rooms[*room].capacity < enrollment;
+        if let Some(priority) = too_small_priority {
+            let culprits: Vec<usize> = section.rooms.iter().map(|elt| elt.room).filter(too_small).collect();
+            if !culprits.is_empty() {
+                criteria.push(Criterion::RoomTooSmall { priority: priority as u8, section: index, rooms: culprits });
+            }
+        } else {
+            section.rooms.retain(|elt| !too_small(&elt.room));
+            if section.rooms.is_empty() {
+                return err(format!(
+                    "section {} has {} students but none of its rooms are big enough",
+                    section_name, enrollment
+                ));
+            }
+        }
+
+        if let (Some(priority), Some(percent)) = (wasted_seats_priority, wasted_seats_percent) {
+            let seat_limit = enrollment * percent as usize / 100;
+            let culprits: Vec<usize> =
+                section.rooms.iter().map(|elt| elt.room).filter(|&room| rooms[room].capacity > seat_limit).collect();
+            if !culprits.is_empty() {
+                criteria.push(Criterion::RoomTooLarge { priority: priority as u8, section: index, rooms: culprits });
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub fn load_conflicts(
     db: &Connection,
     sections: &mut [Section],
@@ -837,6 +1137,239 @@ pub fn load_conflicts(
     Ok(())
 }
 
+// frozen background sections are pinned to their saved room and time
+fn add_background_sections(
+    sections: &mut Vec<Section>,
+    section_index: &mut HashMap<String, usize>,
+    background: Vec<(String, usize, Option<usize>)>,
+) {
+    for (name, time_slot, room) in background {
+        section_index.insert(name.clone(), sections.len());
+        sections.push(Section {
+            name,
+            rooms: room.map(|room| RoomWithOptionalPriority { room, priority: None }).into_iter().collect(),
+            time_slots: vec![TimeSlotWithOptionalPriority { time_slot, priority: None }],
+            faculty: Vec::new(),
+            enrollment: None,
+            pinned: Some((time_slot, room)),
+            baseline: None,
+            background: true,
+            hard_conflicts: Vec::new(),
+            criteria: Vec::new(),
+            neighbors: Vec::new(),
+        });
+    }
+}
+
+// load conflicts between our sections and frozen background sections
+pub fn load_background_conflicts(
+    db: &Connection,
+    sections: &mut [Section],
+    section_index: &HashMap<String, usize>,
+    criteria: &mut Vec<Criterion>,
+    departments: &[String],
+) -> Result<()> {
+    let marks = vec!["?"; departments.len()].join(", ");
+    let mut stmt = db.prepare(format!(
+        "
+            SELECT DISTINCT section_a, section_b, priority
+            FROM conflict_pairs
+            WHERE department_a IN ({marks})
+              AND department_b NOT IN ({marks})
+            ORDER BY section_a, section_b"
+    ))?;
+    stmt.bind_iter(as_values(&double_vec(departments)))?;
+
+    while stmt.next()? == State::Row {
+        let section_a: String = stmt.read(0)?;
+        let section_b: String = stmt.read(1)?;
+        let priority: i64 = stmt.read(2)?;
+
+        // only conflicts between a movable section and a frozen one matter here
+        let (Some(&index_a), Some(&index_b)) = (section_index.get(&section_a), section_index.get(&section_b)) else {
+            continue;
+        };
+        if sections[index_a].background || !sections[index_b].background {
+            continue;
+        }
+        if priority < LEVEL_FOR_HARD_CONFLICT as i64 || priority >= START_LEVEL_FOR_PREFERENCES as i64 {
+            return Err(format!("conflict pair {section_a} vs {section_b} has invalid priority of {priority}").into());
+        }
+        let priority = priority as u8;
+        if priority == LEVEL_FOR_HARD_CONFLICT {
+            sections[index_a].hard_conflicts.push(index_b);
+            sections[index_b].hard_conflicts.push(index_a);
+        } else {
+            criteria.push(Criterion::SoftConflict { priority, sections: [index_a, index_b] });
+        }
+    }
+
+    Ok(())
+}
+
+// load pinned sections from the database and add any given on the command line
+pub fn load_pins(
+    db: &Connection,
+    sections: &mut [Section],
+    section_index: &HashMap<String, usize>,
+    time_slot_index: &HashMap<String, usize>,
+    room_index: &HashMap<String, usize>,
+    extra: &[TweakSpec],
+    departments: &[String],
+) -> Result<()> {
+    let dept_in = dept_clause(departments, &["department".into()], true);
+    let mut stmt = db.prepare(format!(
+        "
+            SELECT section, time_slot, room
+            FROM pinned_sections
+            WHERE section IN (SELECT section FROM sections_to_be_scheduled {})
+            ORDER BY section",
+        dept_in
+    ))?;
+    stmt.bind_iter(as_values(departments))?;
+
+    let mut pins = Vec::new();
+    while stmt.next()? == State::Row {
+        let section: String = stmt.read(0)?;
+        let time_slot: String = stmt.read(1)?;
+        let room: Option<String> = stmt.read(2)?;
+        pins.push((section, time_slot, room));
+    }
+
+    // command-line pins use "-" for no room and override any in the database
+    for spec in extra {
+        let room = if spec.room == "-" { None } else { Some(spec.room.clone()) };
+        pins.push((spec.section.clone(), spec.time_slot.clone(), room));
+    }
+
+    for (section_name, time_slot_name, room_name) in pins {
+        let &section = section_index.get(&section_name).ok_or(format!("pinned section {} not found", section_name))?;
+        let &time_slot = time_slot_index
+            .get(&time_slot_name)
+            .ok_or(format!("section {} pinned to unknown time slot {}", section_name, time_slot_name))?;
+        let room_label = room_name.clone().unwrap_or_else(|| "-".to_string());
+        let room = match room_name {
+            Some(name) => Some(
+                *room_index.get(&name).ok_or(format!("section {} pinned to unknown room {}", section_name, name))?,
+            ),
+            None => None,
+        };
+
+        let elt = &mut sections[section];
+        if elt.background {
+            return err(format!("section {} is frozen in the background placement and cannot be pinned", section_name));
+        }
+        if !elt.time_slots.iter().any(|option| option.time_slot == time_slot) {
+            return err(format!("section {} cannot be pinned to time slot {}", section_name, time_slot_name));
+        }
+        let room_ok = match room {
+            Some(room) => elt.rooms.iter().any(|option| option.room == room),
+            None => elt.rooms.is_empty(),
+        };
+        if !room_ok {
+            return err(format!("section {} cannot be pinned to room {}", section_name, room_label));
+        }
+        elt.pinned = Some((time_slot, room));
+    }
+
+    Ok(())
+}
+
+// pinned sections can never be displaced, so they must not displace each other
+fn check_pins(sections: &[Section], time_slot_conflicts: &[Vec<bool>]) -> Result<()> {
+    for (a, section_a) in sections.iter().enumerate() {
+        let Some((time_slot_a, room_a)) = section_a.pinned else {
+            continue;
+        };
+        for (b, section_b) in sections.iter().enumerate().skip(a + 1) {
+            let Some((time_slot_b, room_b)) = section_b.pinned else {
+                continue;
+            };
+            if !time_slot_conflicts[time_slot_a][time_slot_b] {
+                continue;
+            }
+            if section_a.hard_conflicts.contains(&b) || (room_a.is_some() && room_a == room_b) {
+                return err(format!(
+                    "pinned sections {} and {} conflict with each other",
+                    section_a.name, section_b.name
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// record where the baseline placement put each section and penalize moving it,
+// returning warnings about sections it placed somewhere no longer in use; they are
+// printed once the "loading input data" progress line is finished
+#[allow(clippy::too_many_arguments)]
+fn load_baseline(
+    db: &Connection,
+    sections: &mut [Section],
+    section_index: &HashMap<String, usize>,
+    time_slot_index: &HashMap<String, usize>,
+    room_index: &HashMap<String, usize>,
+    criteria: &mut Vec<Criterion>,
+    baseline_id: i64,
+    priority: u8,
+) -> Result<Vec<String>> {
+    let mut stmt = db.prepare("SELECT placement_id FROM placements WHERE placement_id = ?")?;
+    stmt.bind((1, baseline_id))?;
+    if stmt.next()? != State::Row {
+        return err(format!("baseline placement {} not found", baseline_id));
+    }
+
+    let mut stmt = db.prepare(
+        "
+            SELECT section, time_slot, room
+            FROM placement_sections
+            WHERE placement_id = ?
+            ORDER BY section",
+    )?;
+    stmt.bind((1, baseline_id))?;
+    let mut warnings = Vec::new();
+    while stmt.next()? == State::Row {
+        let section_name: String = stmt.read(0)?;
+        let time_slot_name: String = stmt.read(1)?;
+        let room_name: Option<String> = stmt.read(2)?;
+
+        // sections outside this solve (or frozen in the background) stay put anyway
+        let Some(&section) = section_index.get(&section_name) else {
+            continue;
+        };
+        if sections[section].background {
+            continue;
+        }
+
+        let Some(&time_slot) = time_slot_index.get(&time_slot_name) else {
+            warnings.push(format!(
+                "{} was at time slot {} in the baseline, which is no longer in use",
+                section_name, time_slot_name
+            ));
+            continue;
+        };
+        let room = match room_name {
+            Some(name) => match room_index.get(&name) {
+                Some(&room) => Some(room),
+                None => {
+                    warnings.push(format!(
+                        "{} was in room {} in the baseline, which is no longer in use",
+                        section_name, name
+                    ));
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        sections[section].baseline = Some((time_slot, room));
+        criteria.push(Criterion::SectionMoved { priority, section, time_slot, room });
+    }
+
+    Ok(warnings)
+}
+
 pub fn load_anti_conflicts(
     db: &Connection,
     _sections: &[Section],
@@ -1230,11 +1763,43 @@ fn dept_clause(departments: &[String], columns: &[String], with_where: bool) ->
     s
 }
 
+// placements record which departments they cover so that solves for
+// different slices of the same database do not load each other's work
+pub fn department_scope(departments: &[String]) -> String {
+    departments.join(", ")
+}
+
+// how a saved placement was produced, recorded with it so the run can be
+// reproduced and its lineage explained
+#[derive(Clone)]
+pub struct Provenance {
+    // the subcommand and its full option set as command-line flags
+    pub command: String,
+    pub options: String,
+
+    // the random seed the run started from (if it used one)
+    pub seed: Option<u64>,
+
+    // the placement this one was derived from (if any)
+    pub parent_id: Option<i64>,
+
+    // when the run started, to record how long it had gone at each save
+    pub started: Instant,
+}
+
+impl Provenance {
+    // the seed and parent are filled in once they are known
+    pub fn new(command: &str, options: String) -> Self {
+        Provenance { command: command.to_string(), options, seed: None, parent_id: None, started: Instant::now() }
+    }
+}
+
 pub fn save_schedule(
     db_path: &str,
     input: &Input,
     schedule: &Schedule,
     comment: &str,
+    provenance: &Provenance,
     existing_id: Option<i64>,
 ) -> Result<i64> {
     let db = Connection::open_with_flags(db_path, OpenFlags::new().with_read_write().with_full_mutex())?;
@@ -1243,6 +1808,8 @@ pub fn save_schedule(
     db.execute("BEGIN")?;
     let optimum_score_prefix_json =
         format!("[{}]", schedule.optimum_score_prefix.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(","));
+    let seed = provenance.seed.map(|seed| seed as i64);
+    let wall_ms = provenance.started.elapsed().as_millis() as i64;
     let root_id = if let Some(id) = existing_id {
         // delete old schedule with this id and update base record
         let mut stmt = db.prepare(
@@ -1268,33 +1835,72 @@ pub fn save_schedule(
             optimum_score_prefix = ?,
             faculty_preference_priority_policy = ?,
             comment = ?,
+            command = ?,
+            options = ?,
+            seed = ?,
+            version = ?,
+            wall_ms = ?,
+            parent_placement_id = ?,
             modified_at = DATETIME('now', 'localtime')
-            WHERE placement_id = ?",
+            WHERE placement_id = ?
+              AND department_scope = ?
+              AND background_placement_id IS ?
+              AND baseline_placement_id IS ?
+              AND baseline_priority IS ?",
         )?;
         stmt.bind((1, format!("{}", schedule.score).as_str()))?;
         stmt.bind((2, schedule.score.sortable().as_str()))?;
         stmt.bind((3, optimum_score_prefix_json.as_str()))?;
         stmt.bind((4, input.faculty_preference_priority_policy.database_name()))?;
         stmt.bind((5, comment))?;
-        stmt.bind((6, id))?;
+        stmt.bind((6, provenance.command.as_str()))?;
+        stmt.bind((7, provenance.options.as_str()))?;
+        stmt.bind((8, seed))?;
+        stmt.bind((9, env!("CARGO_PKG_VERSION")))?;
+        stmt.bind((10, wall_ms))?;
+        stmt.bind((11, provenance.parent_id))?;
+        stmt.bind((12, id))?;
+        stmt.bind((13, department_scope(&input.departments).as_str()))?;
+        stmt.bind((14, input.background_id))?;
+        stmt.bind((15, input.baseline.map(|(id, _)| id)))?;
+        stmt.bind((16, input.baseline.map(|(_, priority)| priority as i64)))?;
         if stmt.next()? != State::Done {
             panic!("no rows expected for update");
         }
+        if db.change_count() != 1 {
+            db.execute("ROLLBACK")?;
+            return err(format!(
+                "placement {} does not cover the same departments, background, and baseline as this schedule",
+                id
+            ));
+        }
         id
     } else {
         // create new base record and capture id
         let mut stmt = db.prepare(
             "INSERT INTO placements
                 (score, sort_score, optimum_score_prefix, faculty_preference_priority_policy,
-                 comment, created_at, modified_at)
-            VALUES (?, ?, ?, ?, ?, DATETIME('now', 'localtime'), DATETIME('now', 'localtime'))
+                 department_scope, background_placement_id, baseline_placement_id, baseline_priority,
+                 comment, command, options, seed, version, wall_ms, parent_placement_id, created_at, modified_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                DATETIME('now', 'localtime'), DATETIME('now', 'localtime'))
             RETURNING placement_id",
         )?;
         stmt.bind((1, format!("{}", schedule.score).as_str()))?;
         stmt.bind((2, schedule.score.sortable().as_str()))?;
         stmt.bind((3, optimum_score_prefix_json.as_str()))?;
         stmt.bind((4, input.faculty_preference_priority_policy.database_name()))?;
-        stmt.bind((5, comment))?;
+        stmt.bind((5, department_scope(&input.departments).as_str()))?;
+        stmt.bind((6, input.background_id))?;
+        stmt.bind((7, input.baseline.map(|(id, _)| id)))?;
+        stmt.bind((8, input.baseline.map(|(_, priority)| priority as i64)))?;
+        stmt.bind((9, comment))?;
+        stmt.bind((10, provenance.command.as_str()))?;
+        stmt.bind((11, provenance.options.as_str()))?;
+        stmt.bind((12, seed))?;
+        stmt.bind((13, env!("CARGO_PKG_VERSION")))?;
+        stmt.bind((14, wall_ms))?;
+        stmt.bind((15, provenance.parent_id))?;
         let mut id = -1;
         while stmt.next()? == State::Row {
             id = stmt.read(0)?;
@@ -1309,6 +1915,11 @@ pub fn save_schedule(
             // skip unplaced sections
             continue;
         };
+
+        // background sections belong to the background placement
+        if input.sections[section].background {
+            continue;
+        }
         let mut stmt = db.prepare(
             "INSERT INTO placement_sections (placement_id, section, time_slot, room)
             VALUES (?, ?, ?, ?)",
@@ -1388,17 +1999,22 @@ pub fn save_schedule(
             }
         }
     }
+    save_fingerprints(&db, root_id, input)?;
     db.execute("COMMIT")?;
 
     Ok(root_id)
 }
 
+// load a saved placement into an empty schedule, reporting any way the input
+// has changed since it was saved; returns the ID of the placement loaded and
+// the sections that need to be placed again to repair it: new sections, plus
+// saved sections whose placement is no longer valid (those are left unplaced)
 pub fn load_schedule(
     db_path: &str,
     input: &Input,
     schedule: &mut Schedule,
     maybe_placement_id: Option<i64>,
-) -> Result<()> {
+) -> Result<(i64, Vec<usize>)> {
     let db = Connection::open_with_flags(db_path, OpenFlags::new().with_read_only().with_no_mutex())?;
     db.execute("PRAGMA foreign_keys = ON")?;
     db.execute("PRAGMA temp_store = memory")?;
@@ -1414,6 +2030,10 @@ pub fn load_schedule(
         "SELECT placement_id, score, optimum_score_prefix
         FROM placements
         WHERE faculty_preference_priority_policy = ?
+          AND department_scope = ?
+          AND background_placement_id IS ?
+          AND baseline_placement_id IS ?
+          AND baseline_priority IS ?
           AND (placement_id = ? OR ?)
         ORDER BY sort_score, modified_at DESC
         LIMIT 1",
@@ -1424,17 +2044,33 @@ pub fn load_schedule(
         (0, 1) // search by sort score
     };
     stmt.bind((1, input.faculty_preference_priority_policy.database_name()))?;
-    stmt.bind((2, q_id))?;
-    stmt.bind((3, q_override))?;
+    stmt.bind((2, department_scope(&input.departments).as_str()))?;
+    stmt.bind((3, input.background_id))?;
+    stmt.bind((4, input.baseline.map(|(id, _)| id)))?;
+    stmt.bind((5, input.baseline.map(|(_, priority)| priority as i64)))?;
+    stmt.bind((6, q_id))?;
+    stmt.bind((7, q_override))?;
 
     if let State::Row = stmt.next()? {
         placement_id = stmt.read(0)?;
         saved_score = stmt.read(1)?;
         optimum_score_prefix_json = stmt.read(2)?;
     } else {
+        let mut scope = if input.departments.is_empty() {
+            "all departments".to_string()
+        } else {
+            department_scope(&input.departments)
+        };
+        if let Some(id) = input.background_id {
+            scope = format!("{} against background placement {}", scope, id);
+        }
+        if let Some((id, priority)) = input.baseline {
+            scope = format!("{} relative to baseline placement {} at priority {}", scope, id, priority);
+        }
         return Err(format!(
-            "no placement found for faculty preference priority policy {}",
-            input.faculty_preference_priority_policy.database_name()
+            "no placement found for faculty preference priority policy {} covering {}",
+            input.faculty_preference_priority_policy.database_name(),
+            scope
         )
         .into());
     };
@@ -1451,58 +2087,175 @@ pub fn load_schedule(
     let mut found = false;
     while stmt.next()? == State::Row {
         let modified_at: String = stmt.read(0)?;
-        println!("loading schedule {}, which was last updated at {}", placement_id, modified_at);
+        messageln!("loading schedule {}, which was last updated at {}", placement_id, modified_at);
         found = true;
     }
     if !found {
         return Err(format!("schedule {} not found", placement_id).into());
     }
 
+    // report how the input has changed since the placement was saved
+    let mut repairs = Vec::new();
+    let mut input_changed = false;
+    let mut fingerprinted = false;
+    if let Some(changes) = compare_fingerprints(&db, placement_id, input)? {
+        fingerprinted = true;
+        if !changes.is_empty() {
+            messageln!("the input has changed since placement {} was saved:", placement_id);
+        }
+        for change in &changes {
+            messageln!("    {}", change.describe(input));
+            if let InputChange::Added(section) = *change
+                && !input.sections[section].is_pinned()
+            {
+                repairs.push(section);
+            }
+        }
+        input_changed = !changes.is_empty();
+    }
+
     let mut stmt = db.prepare(
         "SELECT section, time_slot, room
             FROM placement_sections
-            WHERE placement_id = ?",
+            WHERE placement_id = ?
+            ORDER BY section",
     )?;
     stmt.bind((1, placement_id))?;
 
+    // pins that disagree with the saved placement change its score
+    let mut pins_changed = false;
     while stmt.next()? == State::Row {
         let section_name: String = stmt.read(0)?;
         let time_slot_name: String = stmt.read(1)?;
         let maybe_room_name: Option<String> = stmt.read(2)?;
 
-        let Some((section, _)) = input.sections.iter().enumerate().find(|(_, elt)| elt.name == section_name) else {
-            return Err(format!("load_schedule cannot find section {} referenced in placement", section_name).into());
+        let Some(section) = input.sections.iter().position(|elt| elt.name == section_name && !elt.background) else {
+            // the fingerprint comparison already reported it
+            if !fingerprinted {
+                messageln!("{} is not in the input and was dropped", section_name);
+            }
+            input_changed = true;
+            continue;
         };
-        let Some((time_slot, _)) = input.time_slots.iter().enumerate().find(|(_, elt)| elt.name == time_slot_name)
-        else {
-            return Err(
-                format!("load_schedule cannot find time slot {} referenced in placement", time_slot_name).into()
-            );
+        let time_slot = input.time_slots.iter().position(|elt| elt.name == time_slot_name);
+        let maybe_room = match &maybe_room_name {
+            Some(room_name) => input.rooms.iter().position(|elt| &elt.name == room_name).map(Some),
+            None => Some(None),
         };
-        let maybe_room = if let Some(room_name) = maybe_room_name {
-            let Some((room, _)) = input.rooms.iter().enumerate().find(|(_, elt)| elt.name == room_name) else {
-                return Err(format!("load_schedule cannot find room {} referenced in placement", room_name).into());
-            };
-            Some(room)
-        } else {
-            None
+
+        // pinned sections were placed when the schedule was created
+        if let Some(pin) = input.sections[section].pinned {
+            pins_changed |= Some(pin) != time_slot.zip(maybe_room);
+            continue;
+        }
+
+        // saved placements the current input no longer allows are left for repair
+        let (Some(time_slot), Some(maybe_room)) = (time_slot, maybe_room) else {
+            messageln!("{} was placed at a time slot or room that no longer exists and was left unplaced", section_name);
+            input_changed = true;
+            repairs.push(section);
+            continue;
         };
+        if let Some(problem) = placement_problem(input, section, time_slot, maybe_room) {
+            messageln!(
+                "{} at {} {} and was left unplaced",
+                section_name,
+                input.describe_placement(time_slot, maybe_room),
+                problem
+            );
+            input_changed = true;
+            repairs.push(section);
+            continue;
+        }
+
+        // leave sections that collide with a pin for the solver to place
+        match schedule.has_hard_conflict(input, section, time_slot, &maybe_room, &[]) {
+            Some((_, true)) => {
+                messageln!("{} conflicts with a pinned section and was left unplaced", section_name);
+                pins_changed = true;
+                repairs.push(section);
+                continue;
+            }
+            Some((_, false)) => {
+                messageln!("{} now has a hard conflict with another saved section and was left unplaced", section_name);
+                input_changed = true;
+                repairs.push(section);
+                continue;
+            }
+            None => {}
+        }
 
         let _undo = move_section(input, schedule, section, time_slot, &maybe_room);
     }
 
     // does the generated score match the saved score?
-    if format!("{}", schedule.score) != saved_score {
+    if pins_changed || input_changed {
+        messageln!("placement {} was saved with score {} before the current input", placement_id, saved_score);
+    } else if format!("{}", schedule.score) != saved_score {
         return err(format!(
             "for placement with ID {} the saved score of {} does not match the computed score of {}",
             placement_id, saved_score, schedule.score
         ));
     }
+    if !repairs.is_empty() {
+        repairs.sort_unstable();
+        repairs.dedup();
+        messageln!("{} sections need to be placed again (run \"marmot repair\" to fix only those)", repairs.len());
+    }
 
     // parse the SAT-proved score prefix
     schedule.optimum_score_prefix = parse_score_array(&optimum_score_prefix_json)?;
 
-    Ok(())
+    Ok((placement_id, repairs))
+}
+
+// why the input no longer allows a section at a placement, if it does not
+fn placement_problem(input: &Input, section: usize, time_slot: usize, maybe_room: Option<usize>) -> Option<String> {
+    let elt = &input.sections[section];
+    if !elt.time_slots.iter().any(|ts| ts.time_slot == time_slot) {
+        return Some("is no longer allowed at that time".to_string());
+    }
+    match maybe_room {
+        Some(room) if !elt.rooms.iter().any(|r| r.room == room) => {
+            Some("is no longer allowed in that room".to_string())
+        }
+        None if !elt.rooms.is_empty() => Some("now needs a room".to_string()),
+        _ => None,
+    }
+}
+
+// map each secondary cross-listed section name to the primary section that carries its placement
+pub fn load_cross_listings(db_path: &str, input: &Input) -> Result<HashMap<String, usize>> {
+    let db = Connection::open_with_flags(db_path, OpenFlags::new().with_read_only().with_no_mutex())?;
+    db.execute("PRAGMA foreign_keys = ON")?;
+    let mut stmt = db.prepare("SELECT section, primary_section FROM cross_listing_sections ORDER BY section")?;
+
+    let mut cross_listings = HashMap::new();
+    while stmt.next()? == State::Row {
+        let section: String = stmt.read(0)?;
+        let primary: String = stmt.read(1)?;
+        if let Some(index) = input.sections.iter().position(|elt| elt.name == primary) {
+            cross_listings.insert(section, index);
+        }
+    }
+    Ok(cross_listings)
+}
+
+// the term's first and last days and its holidays, as YYYY-MM-DD strings
+pub fn load_term_dates(db_path: &str) -> Result<(String, String, Vec<String>)> {
+    let db = Connection::open_with_flags(db_path, OpenFlags::new().with_read_only().with_no_mutex())?;
+    let mut stmt = db.prepare("SELECT start_date, end_date FROM terms")?;
+    let (start, end) = match stmt.next()? {
+        State::Row => (stmt.read(0)?, stmt.read(1)?),
+        State::Done => return err("no term found in the database"),
+    };
+
+    let mut stmt = db.prepare("SELECT holiday FROM holidays ORDER BY holiday")?;
+    let mut holidays = Vec::new();
+    while stmt.next()? == State::Row {
+        holidays.push(stmt.read(0)?);
+    }
+    Ok((start, end, holidays))
 }
 
 fn parse_score_array(json_str: &str) -> Result<Vec<ScoreLevel>> {
@@ -1513,7 +2266,11 @@ fn parse_score_array(json_str: &str) -> Result<Vec<ScoreLevel>> {
 
     // remove brackets and split on commas
     let mut result = Vec::new();
-    for item in json_str[1..json_str.len() - 1].split(',') {
+    let items = json_str[1..json_str.len() - 1].trim();
+    if items.is_empty() {
+        return Ok(result);
+    }
+    for item in items.split(',') {
         match item.trim().parse::<i16>() {
             Ok(value) => result.push(value as ScoreLevel),
             Err(_) => return err(format!("Failed to parse '{}' as i16", item)),
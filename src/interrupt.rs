@@ -0,0 +1,88 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// the long-running commands catch the first SIGINT or SIGTERM and only set a
+// flag, which the searches check between steps so they can save the best
+// schedule they have and report it as if they had finished normally; a second
+// signal kills the process as usual
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// a background search can also be cancelled on its own: on a thread that has
+// a cancel flag, interrupted() reports that flag too
+thread_local! {
+    static CANCEL: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+#[cfg(unix)]
+mod signals {
+    use std::ffi::c_int;
+
+    pub const SIGINT: c_int = 2;
+    pub const SIGTERM: c_int = 15;
+    pub const SIG_DFL: usize = 0;
+
+    unsafe extern "C" {
+        pub fn signal(signum: c_int, handler: usize) -> usize;
+    }
+
+    pub extern "C" fn handle(signum: c_int) {
+        caught(&super::INTERRUPTED, signum);
+    }
+
+    // what the handler does, with the flag passed in so tests can use their own
+    pub fn caught(flag: &super::AtomicBool, signum: c_int) {
+        flag.store(true, super::Ordering::SeqCst);
+
+        // restore the default so the next signal is not ignored
+        unsafe {
+            signal(signum, SIG_DFL);
+        }
+    }
+}
+
+pub fn catch_interrupts() {
+    #[cfg(unix)]
+    unsafe {
+        let handler = signals::handle as extern "C" fn(std::ffi::c_int) as usize;
+        signals::signal(signals::SIGINT, handler);
+        signals::signal(signals::SIGTERM, handler);
+    }
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst) || CANCEL.with(|cancel| cancel.borrow().as_ref().is_some_and(is_set))
+}
+
+// make interrupted() on this thread also report the given flag
+pub fn cancel_with(flag: Arc<AtomicBool>) {
+    CANCEL.with(|cancel| *cancel.borrow_mut() = Some(flag));
+}
+
+fn is_set(flag: &Arc<AtomicBool>) -> bool {
+    flag.load(Ordering::SeqCst)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    extern "C" fn ignore(_signum: std::ffi::c_int) {}
+
+    // the handler is tested with a flag of its own: a real signal would set the
+    // flag every search shares, and stop any solve running in another test
+    #[test]
+    fn first_signal_only_sets_the_flag() {
+        let flag = AtomicBool::new(false);
+        let stand_in = ignore as extern "C" fn(std::ffi::c_int) as usize;
+        unsafe {
+            signals::signal(signals::SIGTERM, stand_in);
+        }
+        signals::caught(&flag, signals::SIGTERM);
+        let restored = unsafe { signals::signal(signals::SIGTERM, signals::SIG_DFL) };
+        assert!(flag.load(Ordering::SeqCst));
+        assert_eq!(restored, signals::SIG_DFL);
+        assert!(!interrupted());
+    }
+}
@@ -0,0 +1,453 @@
+use super::input::*;
+use super::score::*;
+use super::solver::*;
+use std::fmt;
+use std::fmt::Write;
+
+// a minimal JSON value for machine-readable output
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl From<i64> for Json {
+    fn from(value: i64) -> Self {
+        Json::Number(value)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Number(value as i64)
+    }
+}
+
+impl From<u8> for Json {
+    fn from(value: u8) -> Self {
+        Json::Number(value as i64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(value: Vec<T>) -> Self {
+        Json::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl Json {
+    // a string literal that is also safe to embed in a <script> element
+    pub fn quote(s: &str) -> String {
+        let mut out = String::from("\"");
+        for ch in s.chars() {
+            match ch {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '<' => out.push_str("\\u003c"),
+                ch if (ch as u32) < 0x20 => write!(out, "\\u{:04x}", ch as u32).unwrap(),
+                ch => out.push(ch),
+            }
+        }
+        out.push('"');
+        out
+    }
+
+    // arrays of scalars stay on one line, everything else is indented
+    fn write(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(value) => write!(f, "{}", Json::quote(value)),
+            Json::Array(list) if list.is_empty() => write!(f, "[]"),
+            Json::Array(list) if list.iter().all(|elt| !matches!(elt, Json::Array(_) | Json::Object(_))) => {
+                write!(f, "[")?;
+                for (i, elt) in list.iter().enumerate() {
+                    write!(f, "{}", if i == 0 { "" } else { ", " })?;
+                    elt.write(f, indent)?;
+                }
+                write!(f, "]")
+            }
+            Json::Array(list) => {
+                write!(f, "[")?;
+                for (i, elt) in list.iter().enumerate() {
+                    write!(f, "{}\n{:width$}", if i == 0 { "" } else { "," }, "", width = indent + 2)?;
+                    elt.write(f, indent + 2)?;
+                }
+                write!(f, "\n{:width$}]", "", width = indent)
+            }
+            Json::Object(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    write!(
+                        f,
+                        "{}\n{:width$}{}: ",
+                        if i == 0 { "" } else { "," },
+                        "",
+                        Json::quote(key),
+                        width = indent + 2
+                    )?;
+                    value.write(f, indent + 2)?;
+                }
+                write!(f, "\n{:width$}}}", "", width = indent)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+fn section_names(input: &Input, sections: &[usize]) -> Json {
+    sections.iter().map(|&elt| input.sections[elt].name.as_str()).collect::<Vec<_>>().into()
+}
+
+fn faculty_names(input: &Input, faculty: &[usize]) -> Json {
+    faculty.iter().map(|&elt| input.faculty[elt].name.as_str()).collect::<Vec<_>>().into()
+}
+
+fn room_names(input: &Input, rooms: &[usize]) -> Json {
+    rooms.iter().map(|&elt| input.rooms[elt].name.as_str()).collect::<Vec<_>>().into()
+}
+
+fn time_slot_names(input: &Input, time_slots: &[usize]) -> Json {
+    time_slots.iter().map(|&elt| input.time_slots[elt].name.as_str()).collect::<Vec<_>>().into()
+}
+
+fn score_json(score: &Score) -> Json {
+    let levels = score
+        .levels
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count != 0)
+        .map(|(priority, &count)| Json::Object(vec![("priority", priority.into()), ("count", (count as i64).into())]))
+        .collect();
+    Json::Object(vec![("text", score.to_string().into()), ("levels", Json::Array(levels))])
+}
+
+// a penalty with the rooms, time slots, and other details from its variant
+fn penalty_json(input: &Input, schedule: &Schedule, penalty: &Penalty) -> Json {
+    let (priority, message) = penalty.get_score_message(input, schedule);
+    let sections = penalty.get_sections(input);
+
+    // faculty preferences belong to their owner, everything else to the faculty of the sections involved
+    let mut faculty = penalty.faculty().map_or_else(Vec::new, |owner| vec![owner]);
+    if faculty.is_empty() {
+        for &section in &sections {
+            faculty.extend_from_slice(&input.sections[section].faculty);
+        }
+        faculty.sort_unstable();
+        faculty.dedup();
+    }
+
+    let mut details = Vec::new();
+    let (kind, rooms, time_slots) = match penalty {
+        Penalty::SoftConflict { .. } => ("SoftConflict", Vec::new(), Vec::new()),
+        Penalty::AntiConflict { .. } => ("AntiConflict", Vec::new(), Vec::new()),
+        &Penalty::RoomPreference { room, .. } => ("RoomPreference", vec![room], Vec::new()),
+        &Penalty::TimeSlotPreference { time_slot, .. } => ("TimeSlotPreference", Vec::new(), vec![time_slot]),
+        &Penalty::RoomTooSmall { room, .. } => ("RoomTooSmall", vec![room], Vec::new()),
+        &Penalty::RoomTooLarge { room, .. } => ("RoomTooLarge", vec![room], Vec::new()),
+        &Penalty::SectionMoved { time_slot, room, .. } => ("SectionMoved", room.into_iter().collect(), vec![time_slot]),
+        &Penalty::ClusterTooShort { duration, .. } => {
+            details.push(("minutes", (duration.minutes as i64).into()));
+            ("ClusterTooShort", Vec::new(), Vec::new())
+        }
+        &Penalty::ClusterTooLong { duration, .. } => {
+            details.push(("minutes", (duration.minutes as i64).into()));
+            ("ClusterTooLong", Vec::new(), Vec::new())
+        }
+        &Penalty::GapTooShort { duration, .. } => {
+            details.push(("minutes", (duration.minutes as i64).into()));
+            ("GapTooShort", Vec::new(), Vec::new())
+        }
+        &Penalty::GapTooLong { duration, .. } => {
+            details.push(("minutes", (duration.minutes as i64).into()));
+            ("GapTooLong", Vec::new(), Vec::new())
+        }
+        &Penalty::DaysOff { desired, actual, .. } => {
+            details.push(("desired", desired.into()));
+            details.push(("actual", actual.into()));
+            ("DaysOff", Vec::new(), Vec::new())
+        }
+        Penalty::DaysEvenlySpread { .. } => ("DaysEvenlySpread", Vec::new(), Vec::new()),
+        Penalty::RoomSwitch { rooms, .. } => ("RoomSwitch", rooms.to_vec(), Vec::new()),
+        &Penalty::RoomCount { desired, actual, .. } => {
+            details.push(("desired", desired.into()));
+            details.push(("actual", actual.into()));
+            ("RoomCount", Vec::new(), Vec::new())
+        }
+        Penalty::SectionsWithDifferentTimePatterns { time_slots, .. } => {
+            ("SectionsWithDifferentTimePatterns", Vec::new(), time_slots.clone())
+        }
+    };
+
+    let mut fields = vec![
+        ("priority", priority.into()),
+        ("kind", kind.into()),
+        ("message", message.into()),
+        ("sections", section_names(input, &sections)),
+        ("faculty", faculty_names(input, &faculty)),
+        ("rooms", room_names(input, &rooms)),
+        ("time_slots", time_slot_names(input, &time_slots)),
+    ];
+    fields.extend(details);
+    Json::Object(fields)
+}
+
+// placements, per-level scores, and penalties for a schedule
+pub fn schedule_json(input: &Input, schedule: &Schedule) -> Json {
+    let mut placements = Vec::new();
+    let mut problems = Vec::new();
+    for (section, elt) in input.sections.iter().enumerate() {
+        let placement = &schedule.placements[section];
+        placements.push(Json::Object(vec![
+            ("section", elt.name.as_str().into()),
+            ("time_slot", placement.time_slot.map(|time_slot| input.time_slots[time_slot].name.as_str()).into()),
+            ("room", placement.room.map(|room| input.rooms[room].name.as_str()).into()),
+            ("faculty", faculty_names(input, &elt.faculty)),
+            ("pinned", elt.is_pinned().into()),
+            ("background", elt.background.into()),
+            ("score", score_json(&placement.score)),
+        ]));
+        if placement.time_slot.is_none() {
+            problems.push((
+                LEVEL_FOR_UNPLACED_SECTION,
+                Json::Object(vec![
+                    ("priority", LEVEL_FOR_UNPLACED_SECTION.into()),
+                    ("kind", "Unplaced".into()),
+                    ("message", format!("{} is not placed", elt.name).into()),
+                    ("sections", vec![elt.name.as_str()].into()),
+                    ("faculty", faculty_names(input, &elt.faculty)),
+                    ("rooms", Json::Array(Vec::new())),
+                    ("time_slots", Json::Array(Vec::new())),
+                ]),
+            ));
+        }
+    }
+    for penalty_list in &schedule.penalties {
+        for penalty in penalty_list {
+            problems.push((penalty.get_priority(), penalty_json(input, schedule, penalty)));
+        }
+    }
+    problems.sort_by_key(|(priority, _)| *priority);
+
+    Json::Object(vec![
+        ("term", input.term_name.as_str().into()),
+        ("score", score_json(&schedule.score)),
+        ("placements", Json::Array(placements)),
+        ("problems", Json::Array(problems.into_iter().map(|(_, elt)| elt).collect())),
+    ])
+}
+
+fn criterion_json(input: &Input, criterion: &Criterion) -> Json {
+    let mut fields = Vec::new();
+    let kind = match criterion {
+        &Criterion::SoftConflict { priority, .. } => {
+            fields.push(("priority", priority.into()));
+            "SoftConflict"
+        }
+        &Criterion::AntiConflict { priority, .. } => {
+            fields.push(("priority", priority.into()));
+            "AntiConflict"
+        }
+        Criterion::RoomPreference { rooms_with_priorities, .. } => {
+            let rooms = rooms_with_priorities
+                .iter()
+                .map(|elt| {
+                    Json::Object(vec![
+                        ("room", input.rooms[elt.room].name.as_str().into()),
+                        ("priority", elt.priority.into()),
+                    ])
+                })
+                .collect();
+            fields.push(("rooms", Json::Array(rooms)));
+            "RoomPreference"
+        }
+        Criterion::TimeSlotPreference { time_slots_with_priorities, .. } => {
+            let time_slots = time_slots_with_priorities
+                .iter()
+                .map(|elt| {
+                    Json::Object(vec![
+                        ("time_slot", input.time_slots[elt.time_slot].name.as_str().into()),
+                        ("priority", elt.priority.into()),
+                    ])
+                })
+                .collect();
+            fields.push(("time_slots", Json::Array(time_slots)));
+            "TimeSlotPreference"
+        }
+        Criterion::RoomTooSmall { priority, rooms, .. } => {
+            fields.push(("priority", (*priority).into()));
+            fields.push(("rooms", room_names(input, rooms)));
+            "RoomTooSmall"
+        }
+        Criterion::RoomTooLarge { priority, rooms, .. } => {
+            fields.push(("priority", (*priority).into()));
+            fields.push(("rooms", room_names(input, rooms)));
+            "RoomTooLarge"
+        }
+        &Criterion::SectionMoved { priority, time_slot, room, .. } => {
+            fields.push(("priority", priority.into()));
+            fields.push(("time_slot", input.time_slots[time_slot].name.as_str().into()));
+            fields.push(("room", room.map(|room| input.rooms[room].name.as_str()).into()));
+            "SectionMoved"
+        }
+        &Criterion::FacultyPreference { faculty, .. } => {
+            fields.push(("faculty", input.faculty[faculty].name.as_str().into()));
+            "FacultyPreference"
+        }
+        &Criterion::SectionsWithDifferentTimePatterns { priority, .. } => {
+            fields.push(("priority", priority.into()));
+            "SectionsWithDifferentTimePatterns"
+        }
+        Criterion::OwnedFacultyPreference(preference) => {
+            // priority is after any rebalancing, stated_priority is what the faculty member asked for
+            fields.push(("faculty", input.faculty[preference.faculty].name.as_str().into()));
+            fields.push(("priority", preference.priority.into()));
+            fields.push(("stated_priority", preference.stated_priority.into()));
+            "OwnedFacultyPreference"
+        }
+    };
+
+    let mut out = vec![
+        ("kind", kind.into()),
+        ("sections", section_names(input, &criterion.get_culpable_sections())),
+        ("description", criterion.debug(input).into()),
+    ];
+    out.extend(fields);
+    Json::Object(out)
+}
+
+// the loaded input: rooms, time slots, faculty, sections, and criteria with
+// the priorities the solver actually uses
+pub fn input_json(departments: &[String], input: &Input) -> Json {
+    let rooms = input
+        .rooms
+        .iter()
+        .map(|elt| Json::Object(vec![("name", elt.name.as_str().into()), ("capacity", elt.capacity.into())]))
+        .collect();
+    let time_slots = input
+        .time_slots
+        .iter()
+        .map(|elt| {
+            Json::Object(vec![
+                ("name", elt.name.as_str().into()),
+                ("days", elt.days.to_string().into()),
+                ("start_minutes", (elt.start_time.minutes as i64).into()),
+                ("duration_minutes", (elt.duration.minutes as i64).into()),
+            ])
+        })
+        .collect();
+    let faculty = input
+        .faculty
+        .iter()
+        .map(|elt| {
+            Json::Object(vec![("name", elt.name.as_str().into()), ("sections", section_names(input, &elt.sections))])
+        })
+        .collect();
+    let sections = input
+        .sections
+        .iter()
+        .map(|elt| {
+            let rooms = elt
+                .rooms
+                .iter()
+                .map(|room| {
+                    Json::Object(vec![
+                        ("room", input.rooms[room.room].name.as_str().into()),
+                        ("priority", room.priority.into()),
+                    ])
+                })
+                .collect();
+            let time_slots = elt
+                .time_slots
+                .iter()
+                .map(|time_slot| {
+                    Json::Object(vec![
+                        ("time_slot", input.time_slots[time_slot.time_slot].name.as_str().into()),
+                        ("priority", time_slot.priority.into()),
+                    ])
+                })
+                .collect();
+            let placement = |(time_slot, room): (usize, Option<usize>)| {
+                Json::Object(vec![
+                    ("time_slot", input.time_slots[time_slot].name.as_str().into()),
+                    ("room", room.map(|room| input.rooms[room].name.as_str()).into()),
+                ])
+            };
+            Json::Object(vec![
+                ("name", elt.name.as_str().into()),
+                ("rooms", Json::Array(rooms)),
+                ("time_slots", Json::Array(time_slots)),
+                ("faculty", faculty_names(input, &elt.faculty)),
+                ("enrollment", elt.enrollment.into()),
+                ("pinned", elt.pinned.map_or(Json::Null, placement)),
+                ("baseline", elt.baseline.map_or(Json::Null, placement)),
+                ("background", elt.background.into()),
+                ("hard_conflicts", section_names(input, &elt.hard_conflicts)),
+            ])
+        })
+        .collect();
+    let criteria = input.criteria.iter().map(|elt| criterion_json(input, elt)).collect();
+
+    Json::Object(vec![
+        ("term", input.term_name.as_str().into()),
+        ("departments", departments.iter().map(|elt| elt.as_str()).collect::<Vec<_>>().into()),
+        ("rooms", Json::Array(rooms)),
+        ("time_slots", Json::Array(time_slots)),
+        ("faculty", Json::Array(faculty)),
+        ("sections", Json::Array(sections)),
+        ("criteria", Json::Array(criteria)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_nested_values() {
+        let value = Json::Object(vec![
+            ("name", "a \"b\"".into()),
+            ("list", vec![1usize, 2].into()),
+            ("rows", Json::Array(vec![Json::Object(vec![("x", Json::Null)])])),
+        ]);
+        assert_eq!(
+            value.to_string(),
+            "{\n  \"name\": \"a \\\"b\\\"\",\n  \"list\": [1, 2],\n  \"rows\": [\n    {\n      \"x\": null\n    }\n  ]\n}"
+        );
+    }
+}
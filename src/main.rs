@@ -1,8 +1,15 @@
 pub mod cnf;
 pub mod error;
 pub mod faculty_preferences;
+pub mod fingerprint;
+pub mod html;
+pub mod ical;
 pub mod input;
+pub mod interrupt;
+pub mod json;
+pub mod placements;
 pub mod print;
+pub mod registrar;
 pub mod sat_criteria;
 pub mod sat_encoders;
 pub mod sat_solver;
@@ -10,11 +17,17 @@ pub mod score;
 pub mod solver;
 use self::error::Result;
 use self::faculty_preferences::*;
+use self::html::*;
+use self::ical::*;
 use self::input::*;
+use self::interrupt::*;
+use self::json::*;
+use self::placements::*;
 use self::print::*;
+use self::registrar::*;
 use self::sat_solver::*;
+use self::score::ScoreTarget;
 use self::solver::*;
-use std::collections::HashMap;
 use std::time::Instant;
 
 static DEFAULT_DB_PATH: &str = "../data/timetable.db";
@@ -27,64 +40,115 @@ fn main() {
 
 fn dispatch_subcommands() -> Result<()> {
     match parse_args() {
-        Ok(Opts::Gen(config)) => {
+        Ok(Opts::Gen(mut config)) => {
+            catch_interrupts();
+            if config.seed == 0 {
+                config.seed = fastrand::u64(1..);
+            }
+            println!("random seed: {}", config.seed);
+            let mut rng = fastrand::Rng::with_seed(config.seed);
+            let mut provenance = Provenance::new("gen", config.options());
+            provenance.seed = Some(config.seed);
             let input = load_input(
                 &config.db_path,
-                &[],
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &config.pins,
                 config.faculty_preference_priority_policy(),
                 config.show_faculty_preference_priorities(),
             )?;
             let mut id = None;
+            let mut starts = Vec::new();
             let mut schedule = if config.starting_id >= 0 {
                 let mut schedule = Schedule::new(&input);
-                load_schedule(
+                let (parent_id, _) = load_schedule(
                     &config.db_path,
                     &input,
                     &mut schedule,
                     if config.starting_id == 0 { None } else { Some(config.starting_id) },
                 )?;
+                provenance.parent_id = Some(parent_id);
                 schedule
             } else {
-                println!("running warmup for {}", sec_to_string(config.warmup_seconds));
-                let Some(schedule) = warmup(&input, config.warmup_seconds) else {
+                if config.warmup_tries > 0 {
+                    println!("running warmup for {} tries", commas(config.warmup_tries));
+                } else {
+                    println!("running warmup for {}", sec_to_string(config.warmup_seconds));
+                }
+                starts = parallel_warmup(&config, &input, &mut rng);
+
+                // ties go to the lowest-numbered thread
+                let Some(schedule) = starts.iter().flatten().min_by_key(|schedule| schedule.score).cloned() else {
                     return Err("failed to generate a schedule in the warmup stage".into());
                 };
-                id = Some(save_schedule(&config.db_path, &input, &schedule, "warmup schedule", None)?);
+                let comment = if interrupted() { "warmup schedule, interrupted" } else { "warmup schedule" };
+                id = Some(save_schedule(&config.db_path, &input, &schedule, comment, &provenance, None)?);
                 schedule
             };
-            let best = solve(&config, &input, &mut schedule, config.solve_seconds, &mut id);
+            let best =
+                solve(&config, &input, &mut schedule, starts, config.solve_seconds, &provenance, &mut id, &mut rng);
             print_schedule(&input, &best);
             print_problems(&input, &best);
+            print_changes(&input, &best);
+            if best.score.unplaced() > 0 {
+                println!("some sections could not be placed (run \"marmot diagnose\" to check the hard constraints)");
+            }
             Ok(())
         }
 
         Ok(Opts::Sat(config)) => {
+            catch_interrupts();
+            let provenance = Provenance::new("sat", config.options());
             let input = load_input(
                 &config.db_path,
-                &[],
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &config.pins,
                 config.faculty_preference_priority_policy(),
                 config.show_faculty_preference_priorities(),
             )?;
-            let schedule = generate_schedule(&config, &input)?;
+            let schedule = generate_schedule(&config, &input, &provenance)?;
             //print_schedule(&input, &schedule);
             print_problems(&input, &schedule);
+            print_changes(&input, &schedule);
             Ok(())
         }
 
+        Ok(Opts::Diagnose(config)) => {
+            let input = load_input(
+                &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &config.pins,
+                config.faculty_preference_priority_policy(),
+                config.show_faculty_preference_priorities(),
+            )?;
+            diagnose_hard_constraints(&input)
+        }
+
         Ok(Opts::Dfs(config)) => {
+            catch_interrupts();
+            let mut provenance = Provenance::new("dfs", config.options());
             let input = load_input(
                 &config.db_path,
-                &[],
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &config.pins,
                 config.faculty_preference_priority_policy(),
                 config.show_faculty_preference_priorities(),
             )?;
             let mut schedule = Schedule::new(&input);
-            load_schedule(
+            let (parent_id, _) = load_schedule(
                 &config.db_path,
                 &input,
                 &mut schedule,
                 if config.starting_id == 0 { None } else { Some(config.starting_id) },
             )?;
+            provenance.parent_id = Some(parent_id);
             let pre_score = schedule.score;
             let mut save_id = None;
             let mut iterations = 0;
@@ -97,13 +161,18 @@ fn dispatch_subcommands() -> Result<()> {
                 iterations += 1;
                 if schedule.score < before {
                     let comment = format!(
-                        "dfs at depth {}, {} iteration{} over {}",
+                        "dfs at depth {}, {} iteration{} over {}{}",
                         config.dfs_depth,
                         iterations,
                         if iterations == 1 { "" } else { "s" },
-                        ms_to_string(start.elapsed().as_millis())
+                        ms_to_string(start.elapsed().as_millis()),
+                        if interrupted() { ", interrupted" } else { "" }
                     );
-                    save_id = Some(save_schedule(&config.db_path, &input, &schedule, &comment, save_id)?);
+                    save_id = Some(save_schedule(&config.db_path, &input, &schedule, &comment, &provenance, save_id)?);
+                }
+                if interrupted() {
+                    println!("interrupted, stopping dfs");
+                    break;
                 }
                 if schedule.score >= before || !config.repeat {
                     break;
@@ -112,12 +181,56 @@ fn dispatch_subcommands() -> Result<()> {
             if schedule.score < pre_score {
                 println!("score improved from {} to {} over {} iterations", pre_score, schedule.score, iterations);
             }
+            print_changes(&input, &schedule);
+            Ok(())
+        }
+
+        Ok(Opts::Lns(mut config)) => {
+            catch_interrupts();
+            if config.seed == 0 {
+                config.seed = fastrand::u64(1..);
+            }
+            println!("random seed: {}", config.seed);
+            let mut rng = fastrand::Rng::with_seed(config.seed);
+            let mut provenance = Provenance::new("lns", config.options());
+            provenance.seed = Some(config.seed);
+            let input = load_input(
+                &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &config.pins,
+                config.faculty_preference_priority_policy(),
+                config.show_faculty_preference_priorities(),
+            )?;
+            let mut schedule = Schedule::new(&input);
+            let (parent_id, _) = load_schedule(
+                &config.db_path,
+                &input,
+                &mut schedule,
+                if config.starting_id == 0 { None } else { Some(config.starting_id) },
+            )?;
+            provenance.parent_id = Some(parent_id);
+            let pre_score = schedule.score;
+            large_neighborhood_search(&config, &input, &mut schedule, &provenance, &mut rng)?;
+            if schedule.score < pre_score {
+                println!("score improved from {} to {}", pre_score, schedule.score);
+            }
+            print_schedule(&input, &schedule);
+            print_problems(&input, &schedule);
+            print_changes(&input, &schedule);
             Ok(())
         }
 
         Ok(Opts::Print(config)) => {
+            if config.format == "json" {
+                send_messages_to_stderr();
+            }
             let input = load_input(
                 &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
                 &[],
                 config.faculty_preference_priority_policy(),
                 config.show_faculty_preference_priorities(),
@@ -129,32 +242,243 @@ fn dispatch_subcommands() -> Result<()> {
                 &mut schedule,
                 if config.starting_id == 0 { None } else { Some(config.starting_id) },
             )?;
+            if config.format == "json" {
+                println!("{}", schedule_json(&input, &schedule));
+                return Ok(());
+            }
             println!("score: {}", schedule.score);
             print_schedule(&input, &schedule);
             print_problems(&input, &schedule);
+            print_changes(&input, &schedule);
+            Ok(())
+        }
+
+        Ok(Opts::Repair(config)) => {
+            let mut provenance = Provenance::new("repair", config.options());
+            let input = load_input(
+                &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &[],
+                config.faculty_preference_priority_policy(),
+                config.show_faculty_preference_priorities(),
+            )?;
+            let mut schedule = Schedule::new(&input);
+            let (parent_id, repairs) = load_schedule(
+                &config.db_path,
+                &input,
+                &mut schedule,
+                if config.starting_id == 0 { None } else { Some(config.starting_id) },
+            )?;
+            if repairs.is_empty() {
+                println!("nothing to repair");
+                return Ok(());
+            }
+            let before = schedule.score;
+            let stuck = repair_sections(&input, &mut schedule, &repairs);
+            for &section in &repairs {
+                if let Some(time_slot) = schedule.placements[section].time_slot {
+                    let room = schedule.placements[section].room;
+                    println!(
+                        "placed {} at {}",
+                        input.sections[section].name,
+                        input.describe_placement(time_slot, room)
+                    );
+                }
+            }
+            for &section in &stuck {
+                println!("{} could not be placed without moving other sections", input.sections[section].name);
+            }
+            if stuck.len() == repairs.len() {
+                println!("nothing could be repaired (run \"marmot gen\" to re-solve around these sections)");
+                return Ok(());
+            }
+            let comment = format!("repaired {} of {} sections", repairs.len() - stuck.len(), repairs.len());
+            provenance.parent_id = Some(parent_id);
+            save_schedule(&config.db_path, &input, &schedule, &comment, &provenance, None)?;
+            println!("score went from {} to {}", before, schedule.score);
+            print_problems(&input, &schedule);
+            Ok(())
+        }
+
+        Ok(Opts::Diff(config)) => {
+            let input = load_input(
+                &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &[],
+                config.faculty_preference_priority_policy(),
+                config.show_faculty_preference_priorities(),
+            )?;
+            let faculty = match &config.faculty {
+                Some(name) => Some(
+                    input
+                        .faculty
+                        .iter()
+                        .position(|elt| &elt.name == name)
+                        .ok_or_else(|| format!("Faculty '{}' not found", name))?,
+                ),
+                None => None,
+            };
+            let mut old = Schedule::new(&input);
+            load_schedule(&config.db_path, &input, &mut old, Some(config.old_id))?;
+            let mut new = Schedule::new(&input);
+            load_schedule(&config.db_path, &input, &mut new, Some(config.new_id))?;
+            print_diff(&input, &old, &new, faculty);
+            Ok(())
+        }
+
+        Ok(Opts::Import(config)) => {
+            let provenance = Provenance::new("import", config.options());
+            let input = load_input(
+                &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &[],
+                config.faculty_preference_priority_policy(),
+                config.show_faculty_preference_priorities(),
+            )?;
+            let rows = read_registrar_csv(&config.file)?;
+            println!("read {} rows from {}", rows.len(), config.file);
+            let schedule = import_schedule(&input, &rows);
+            let comment = format!("imported from {}", config.file);
+            let id = save_schedule(&config.db_path, &input, &schedule, &comment, &provenance, None)?;
+            println!("saved imported schedule as placement {}", id);
+            println!("score: {}", schedule.score);
+            print_problems(&input, &schedule);
+            Ok(())
+        }
+
+        Ok(Opts::Export(config)) => {
+            let input = load_input(
+                &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &[],
+                config.faculty_preference_priority_policy(),
+                config.show_faculty_preference_priorities(),
+            )?;
+            let mut schedule = Schedule::new(&input);
+            load_schedule(
+                &config.db_path,
+                &input,
+                &mut schedule,
+                if config.starting_id == 0 { None } else { Some(config.starting_id) },
+            )?;
+            let cross_listings = load_cross_listings(&config.db_path, &input)?;
+            let mut sheet = RegistrarSheet::read(&config.template)?;
+            export_schedule(&input, &schedule, &mut sheet, &cross_listings)?;
+            sheet.write(&config.output)?;
+            println!("wrote {}", config.output);
+            Ok(())
+        }
+
+        Ok(Opts::Html(config)) => {
+            let input = load_input(
+                &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &[],
+                config.faculty_preference_priority_policy(),
+                config.show_faculty_preference_priorities(),
+            )?;
+            let mut schedule = Schedule::new(&input);
+            load_schedule(
+                &config.db_path,
+                &input,
+                &mut schedule,
+                if config.starting_id == 0 { None } else { Some(config.starting_id) },
+            )?;
+            let cross_listings = load_cross_listings(&config.db_path, &input)?;
+            let title = if config.starting_id == 0 {
+                format!("Marmot: best schedule {}", schedule.score)
+            } else {
+                format!("Marmot: schedule {} {}", config.starting_id, schedule.score)
+            };
+            write_html(&input, &schedule, &cross_listings, &title, &config.output)?;
+            println!("wrote {}", config.output);
+            if !config.data.is_empty() {
+                write_placement_data(&input, &schedule, &cross_listings, &config.data)?;
+                println!("wrote {}", config.data);
+            }
+            Ok(())
+        }
+
+        Ok(Opts::Ical(config)) => {
+            let input = load_input(
+                &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
+                &[],
+                config.faculty_preference_priority_policy(),
+                config.show_faculty_preference_priorities(),
+            )?;
+            let mut schedule = Schedule::new(&input);
+            load_schedule(
+                &config.db_path,
+                &input,
+                &mut schedule,
+                if config.starting_id == 0 { None } else { Some(config.starting_id) },
+            )?;
+            let cross_listings = load_cross_listings(&config.db_path, &input)?;
+            let (start, end, holidays) = load_term_dates(&config.db_path)?;
+            let term = TermCalendar::new(&start, &end, &holidays)?;
+            write_ical(&input, &schedule, &term, &cross_listings, &config.output)?;
             Ok(())
         }
 
+        Ok(Opts::List(config)) => list_placements(&config.db_path),
+
+        Ok(Opts::Label(config)) => label_placement(&config.db_path, config.placement_id, &config.label),
+
+        Ok(Opts::Publish(config)) => publish_placement(&config.db_path, config.placement_id),
+
+        Ok(Opts::Show(config)) => {
+            show_placement(&config.db_path, config.placement_id, &department_scope(&config.departments))
+        }
+
+        Ok(Opts::Prune(config)) => prune_placements(&config.db_path, config.keep, config.dry_run),
+
         Ok(Opts::Dump(config)) => {
+            if config.format == "json" {
+                send_messages_to_stderr();
+            }
             let input = load_input(
                 &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
                 &[],
                 config.faculty_preference_priority_policy(),
                 config.show_faculty_preference_priorities(),
             )?;
-            dump_input(&[], &input);
+            if config.format == "json" {
+                println!("{}", input_json(&config.departments, &input));
+            } else {
+                dump_input(&config.departments, &input);
+            }
             Ok(())
         }
 
         Ok(Opts::Tweak(config)) => {
+            let mut provenance = Provenance::new("tweak", config.options());
             let input = load_input(
                 &config.db_path,
+                &config.departments,
+                config.background(),
+                config.baseline(),
                 &[],
                 config.faculty_preference_priority_policy(),
                 config.show_faculty_preference_priorities(),
             )?;
             let mut schedule = Schedule::new(&input);
-            load_schedule(
+            let (parent_id, _) = load_schedule(
                 &config.db_path,
                 &input,
                 &mut schedule,
@@ -197,6 +521,15 @@ fn dispatch_subcommands() -> Result<()> {
             for (section_idx, time_slot_idx, room_idx) in &parsed_tweaks {
                 let section = &input.sections[*section_idx];
 
+                // Pinned sections stay put and cannot be displaced
+                if section.is_pinned() {
+                    return Err(format!("Section '{}' is pinned and cannot be moved", section.name).into());
+                }
+                if let Some((_, true)) = schedule.has_hard_conflict(&input, *section_idx, *time_slot_idx, room_idx, &[])
+                {
+                    return Err(format!("Moving section '{}' would displace a pinned section", section.name).into());
+                }
+
                 // Check if time slot is valid for this section
                 if !section.time_slots.iter().any(|ts| ts.time_slot == *time_slot_idx) {
                     return Err(format!(
@@ -236,7 +569,8 @@ fn dispatch_subcommands() -> Result<()> {
             let comment = format!("tweaked: {}", tweak_descriptions.join("; "));
 
             // Save the new schedule
-            save_schedule(&config.db_path, &input, &schedule, &comment, None)?;
+            provenance.parent_id = Some(parent_id);
+            save_schedule(&config.db_path, &input, &schedule, &comment, &provenance, None)?;
 
             // Print the result
             print_schedule(&input, &schedule);
@@ -258,6 +592,11 @@ fn parse_args() -> Result<Opts> {
         "gen" => {
             let mut opts = GenOpts::default();
             parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            while parser.tweak_specs("", "--pin", &mut opts.pins)? {}
             parser.duration("-w", "--warmup", &mut opts.warmup_seconds)?;
             parser.int64("-i", "--id", &mut opts.starting_id)?;
             parser.duration("-t", "--time", &mut opts.solve_seconds)?;
@@ -269,6 +608,21 @@ fn parse_args() -> Result<Opts> {
             parser.float("-s", "--bias-step", &mut opts.bias_step)?;
             parser.uint("-p", "--dfs-depth", &mut opts.dfs_depth)?;
             parser.boolean("-f", "--fallback", &mut opts.fallback)?;
+            parser.uint("-j", "--threads", &mut opts.threads)?;
+            parser.strategy("", "--strategy", &mut opts.strategy)?;
+            parser.float("", "--anneal-final", &mut opts.anneal_final)?;
+            parser.uint("", "--late-length", &mut opts.late_length)?;
+            parser.uint64("", "--seed", &mut opts.seed)?;
+            parser.uint64("", "--iterations", &mut opts.iterations)?;
+            parser.uint64("", "--warmup-tries", &mut opts.warmup_tries)?;
+            parser.uint64("", "--stall", &mut opts.stall)?;
+            let mut target = String::new();
+            parser.string("", "--target", &mut target)?;
+            if !target.is_empty() {
+                opts.target = Some(ScoreTarget::parse(&target)?);
+            }
+            parser.boolean("", "--stop-at-optimum", &mut opts.stop_at_optimum)?;
+            parser.boolean("", "--prove-bound", &mut opts.prove_bound)?;
             parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
             parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
             parser.leftover()?;
@@ -278,15 +632,40 @@ fn parse_args() -> Result<Opts> {
         "sat" => {
             let mut opts = SatOpts::default();
             parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            while parser.tweak_specs("", "--pin", &mut opts.pins)? {}
+            parser.sat_search("-s", "--search", &mut opts.search)?;
             parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
             parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
             parser.leftover()?;
             Ok(Opts::Sat(opts))
         }
 
+        "diagnose" => {
+            let mut opts = DiagnoseOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            while parser.tweak_specs("", "--pin", &mut opts.pins)? {}
+            parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
+            parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
+            parser.leftover()?;
+            Ok(Opts::Diagnose(opts))
+        }
+
         "dfs" => {
             let mut opts = DfsOpts::default();
             parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            while parser.tweak_specs("", "--pin", &mut opts.pins)? {}
             parser.int64("-i", "--id", &mut opts.starting_id)?;
             parser.uint("-p", "--dfs-depth", &mut opts.dfs_depth)?;
             parser.boolean("-r", "--repeat", &mut opts.repeat)?;
@@ -296,21 +675,206 @@ fn parse_args() -> Result<Opts> {
             Ok(Opts::Dfs(opts))
         }
 
+        "lns" => {
+            let mut opts = LnsOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            while parser.tweak_specs("", "--pin", &mut opts.pins)? {}
+            parser.int64("-i", "--id", &mut opts.starting_id)?;
+            parser.duration("-t", "--time", &mut opts.solve_seconds)?;
+            parser.uint64("", "--iterations", &mut opts.iterations)?;
+            parser.duration("-u", "--update", &mut opts.update_seconds)?;
+            parser.uint("", "--size", &mut opts.size)?;
+            parser.sat_search("-s", "--search", &mut opts.search)?;
+            parser.uint64("", "--seed", &mut opts.seed)?;
+            parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
+            parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
+            parser.leftover()?;
+            Ok(Opts::Lns(opts))
+        }
+
         "print" => {
             let mut opts = PrintOpts::default();
             parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
             parser.int64("-i", "--id", &mut opts.starting_id)?;
+            parser.string("", "--format", &mut opts.format)?;
             parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
             parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
+            check_format(&opts.format)?;
             parser.leftover()?;
             Ok(Opts::Print(opts))
         }
 
+        "repair" => {
+            let mut opts = RepairOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            parser.int64("-i", "--id", &mut opts.starting_id)?;
+            parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
+            parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
+            parser.leftover()?;
+            Ok(Opts::Repair(opts))
+        }
+
+        "diff" => {
+            let mut opts = DiffOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            parser.int64("-a", "--old", &mut opts.old_id)?;
+            parser.int64("-b", "--new", &mut opts.new_id)?;
+            let mut faculty = String::new();
+            parser.string("-f", "--faculty", &mut faculty)?;
+            if !faculty.is_empty() {
+                opts.faculty = Some(faculty);
+            }
+            parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
+            parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
+            if opts.old_id <= 0 || opts.new_id <= 0 {
+                return Err("Error: both placements must be specified with -a/--old and -b/--new".into());
+            }
+            parser.leftover()?;
+            Ok(Opts::Diff(opts))
+        }
+
+        "import" => {
+            let mut opts = ImportOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            parser.string("-f", "--file", &mut opts.file)?;
+            parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
+            parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
+            if opts.file.is_empty() {
+                return Err("Error: the registrar CSV file must be specified with -f/--file".into());
+            }
+            parser.leftover()?;
+            Ok(Opts::Import(opts))
+        }
+
+        "export" => {
+            let mut opts = ExportOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            parser.int64("-i", "--id", &mut opts.starting_id)?;
+            parser.string("-t", "--template", &mut opts.template)?;
+            parser.string("-o", "--output", &mut opts.output)?;
+            parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
+            parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
+            if opts.template.is_empty() || opts.output.is_empty() {
+                return Err("Error: both -t/--template and -o/--output must be specified".into());
+            }
+            parser.leftover()?;
+            Ok(Opts::Export(opts))
+        }
+
+        "html" => {
+            let mut opts = HtmlOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            parser.int64("-i", "--id", &mut opts.starting_id)?;
+            parser.string("-o", "--output", &mut opts.output)?;
+            parser.string("", "--data", &mut opts.data)?;
+            parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
+            parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
+            parser.leftover()?;
+            Ok(Opts::Html(opts))
+        }
+
+        "ical" => {
+            let mut opts = IcalOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            parser.int64("-i", "--id", &mut opts.starting_id)?;
+            parser.string("-o", "--output", &mut opts.output)?;
+            parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
+            parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
+            parser.leftover()?;
+            Ok(Opts::Ical(opts))
+        }
+
+        "list" => {
+            let mut opts = ListOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.leftover()?;
+            Ok(Opts::List(opts))
+        }
+
+        "label" => {
+            let mut opts = LabelOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.int64("-i", "--id", &mut opts.placement_id)?;
+            parser.string("-n", "--name", &mut opts.label)?;
+            if opts.placement_id <= 0 {
+                return Err("Error: the placement must be specified with -i/--id".into());
+            }
+            parser.leftover()?;
+            Ok(Opts::Label(opts))
+        }
+
+        "publish" => {
+            let mut opts = PublishOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.int64("-i", "--id", &mut opts.placement_id)?;
+            if opts.placement_id <= 0 {
+                return Err("Error: the placement must be specified with -i/--id".into());
+            }
+            parser.leftover()?;
+            Ok(Opts::Publish(opts))
+        }
+
+        "show" => {
+            let mut opts = ShowOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("-i", "--id", &mut opts.placement_id)?;
+            parser.leftover()?;
+            Ok(Opts::Show(opts))
+        }
+
+        "prune" => {
+            let mut opts = PruneOpts::default();
+            parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.uint("-k", "--keep", &mut opts.keep)?;
+            parser.boolean("-n", "--dry-run", &mut opts.dry_run)?;
+            parser.leftover()?;
+            Ok(Opts::Prune(opts))
+        }
+
         "dump" => {
             let mut opts = DumpOpts::default();
             parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
+            parser.string("", "--format", &mut opts.format)?;
             parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
             parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
+            check_format(&opts.format)?;
             parser.leftover()?;
             Ok(Opts::Dump(opts))
         }
@@ -318,6 +882,10 @@ fn parse_args() -> Result<Opts> {
         "tweak" => {
             let mut opts = TweakOpts::default();
             parser.string("-d", "--db-path", &mut opts.db_path)?;
+            parser.strings("", "--department", &mut opts.departments)?;
+            parser.int64("", "--background", &mut opts.background_id)?;
+            parser.int64("", "--baseline", &mut opts.baseline_id)?;
+            parser.uint("", "--baseline-priority", &mut opts.baseline_priority)?;
             parser.int64("-i", "--id", &mut opts.starting_id)?;
             parser.boolean("", "--balance-faculty-preferences", &mut opts.balance_faculty_preferences)?;
             parser.boolean("", "--show-faculty-preference-priorities", &mut opts.show_faculty_preference_priorities)?;
@@ -336,14 +904,32 @@ fn parse_args() -> Result<Opts> {
 enum Opts {
     Gen(GenOpts),
     Sat(SatOpts),
+    Diagnose(DiagnoseOpts),
     Dfs(DfsOpts),
+    Lns(LnsOpts),
     Print(PrintOpts),
+    Repair(RepairOpts),
+    Diff(DiffOpts),
+    Import(ImportOpts),
+    Export(ExportOpts),
+    Html(HtmlOpts),
+    Ical(IcalOpts),
+    List(ListOpts),
+    Label(LabelOpts),
+    Publish(PublishOpts),
+    Show(ShowOpts),
+    Prune(PruneOpts),
     Dump(DumpOpts),
     Tweak(TweakOpts),
 }
 
 pub struct GenOpts {
     pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub pins: Vec<TweakSpec>,
     pub warmup_seconds: u64,
     pub starting_id: i64,
     pub solve_seconds: u64,
@@ -355,6 +941,17 @@ pub struct GenOpts {
     pub bias_step: f64,
     pub dfs_depth: usize,
     pub fallback: bool,
+    pub threads: usize,
+    pub strategy: SearchStrategy,
+    pub anneal_final: f64,
+    pub late_length: usize,
+    pub seed: u64,
+    pub iterations: u64,
+    pub warmup_tries: u64,
+    pub stall: u64,
+    pub target: Option<ScoreTarget>,
+    pub stop_at_optimum: bool,
+    pub prove_bound: bool,
     pub balance_faculty_preferences: bool,
     pub show_faculty_preference_priorities: bool,
 }
@@ -363,6 +960,11 @@ impl Default for GenOpts {
     fn default() -> Self {
         Self {
             db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            pins: Vec::new(),
             warmup_seconds: 1,
             starting_id: -1,
             solve_seconds: 30 * 60,
@@ -374,6 +976,17 @@ impl Default for GenOpts {
             bias_step: 0.125,
             dfs_depth: 2,
             fallback: false,
+            threads: 1,
+            strategy: SearchStrategy::Walk,
+            anneal_final: 0.01,
+            late_length: 50_000,
+            seed: 0,
+            iterations: 0,
+            warmup_tries: 0,
+            stall: 0,
+            target: None,
+            stop_at_optimum: true,
+            prove_bound: false,
             balance_faculty_preferences: true,
             show_faculty_preference_priorities: false,
         }
@@ -382,6 +995,12 @@ impl Default for GenOpts {
 
 pub struct SatOpts {
     pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub pins: Vec<TweakSpec>,
+    pub search: SatSearch,
     pub balance_faculty_preferences: bool,
     pub show_faculty_preference_priorities: bool,
 }
@@ -390,74 +1009,406 @@ impl Default for SatOpts {
     fn default() -> Self {
         Self {
             db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            pins: Vec::new(),
+            search: SatSearch::Linear,
             balance_faculty_preferences: true,
             show_faculty_preference_priorities: false,
         }
     }
 }
 
-pub struct PrintOpts {
+pub struct DiagnoseOpts {
     pub db_path: String,
-    pub starting_id: i64,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub pins: Vec<TweakSpec>,
     pub balance_faculty_preferences: bool,
     pub show_faculty_preference_priorities: bool,
 }
 
-impl Default for PrintOpts {
+impl Default for DiagnoseOpts {
     fn default() -> Self {
         Self {
             db_path: DEFAULT_DB_PATH.to_string(),
-            starting_id: 0,
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            pins: Vec::new(),
             balance_faculty_preferences: true,
             show_faculty_preference_priorities: false,
         }
     }
 }
 
-pub struct DumpOpts {
+pub struct PrintOpts {
     pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub starting_id: i64,
+    pub format: String,
     pub balance_faculty_preferences: bool,
     pub show_faculty_preference_priorities: bool,
 }
 
-impl Default for DumpOpts {
+impl Default for PrintOpts {
     fn default() -> Self {
         Self {
             db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            starting_id: 0,
+            format: "text".to_string(),
             balance_faculty_preferences: true,
             show_faculty_preference_priorities: false,
         }
     }
 }
 
-#[derive(Debug)]
-pub struct TweakSpec {
-    pub section: String,
-    pub room: String,
-    pub time_slot: String,
-}
-
-pub struct TweakOpts {
+pub struct RepairOpts {
     pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
     pub starting_id: i64,
-    pub tweaks: Vec<TweakSpec>,
     pub balance_faculty_preferences: bool,
     pub show_faculty_preference_priorities: bool,
 }
 
-pub struct DfsOpts {
-    pub db_path: String,
-    pub starting_id: i64,
-    pub dfs_depth: usize,
-    pub repeat: bool,
-    pub balance_faculty_preferences: bool,
-    pub show_faculty_preference_priorities: bool,
-}
+impl Default for RepairOpts {
+    fn default() -> Self {
+        Self {
+            db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            starting_id: 0,
+            balance_faculty_preferences: true,
+            show_faculty_preference_priorities: false,
+        }
+    }
+}
+
+pub struct DiffOpts {
+    pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub old_id: i64,
+    pub new_id: i64,
+    pub faculty: Option<String>,
+    pub balance_faculty_preferences: bool,
+    pub show_faculty_preference_priorities: bool,
+}
+
+impl Default for DiffOpts {
+    fn default() -> Self {
+        Self {
+            db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            old_id: 0,
+            new_id: 0,
+            faculty: None,
+            balance_faculty_preferences: true,
+            show_faculty_preference_priorities: false,
+        }
+    }
+}
+
+pub struct ImportOpts {
+    pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub file: String,
+    pub balance_faculty_preferences: bool,
+    pub show_faculty_preference_priorities: bool,
+}
+
+impl Default for ImportOpts {
+    fn default() -> Self {
+        Self {
+            db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            file: String::new(),
+            balance_faculty_preferences: true,
+            show_faculty_preference_priorities: false,
+        }
+    }
+}
+
+pub struct ExportOpts {
+    pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub starting_id: i64,
+    pub template: String,
+    pub output: String,
+    pub balance_faculty_preferences: bool,
+    pub show_faculty_preference_priorities: bool,
+}
+
+impl Default for ExportOpts {
+    fn default() -> Self {
+        Self {
+            db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            starting_id: 0,
+            template: String::new(),
+            output: String::new(),
+            balance_faculty_preferences: true,
+            show_faculty_preference_priorities: false,
+        }
+    }
+}
+
+pub struct HtmlOpts {
+    pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub starting_id: i64,
+    pub output: String,
+    pub data: String,
+    pub balance_faculty_preferences: bool,
+    pub show_faculty_preference_priorities: bool,
+}
+
+impl Default for HtmlOpts {
+    fn default() -> Self {
+        Self {
+            db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            starting_id: 0,
+            output: "schedule.html".to_string(),
+            data: String::new(),
+            balance_faculty_preferences: true,
+            show_faculty_preference_priorities: false,
+        }
+    }
+}
+
+pub struct IcalOpts {
+    pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub starting_id: i64,
+    pub output: String,
+    pub balance_faculty_preferences: bool,
+    pub show_faculty_preference_priorities: bool,
+}
+
+impl Default for IcalOpts {
+    fn default() -> Self {
+        Self {
+            db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            starting_id: 0,
+            output: "calendars".to_string(),
+            balance_faculty_preferences: true,
+            show_faculty_preference_priorities: false,
+        }
+    }
+}
+
+pub struct ListOpts {
+    pub db_path: String,
+}
+
+impl Default for ListOpts {
+    fn default() -> Self {
+        Self { db_path: DEFAULT_DB_PATH.to_string() }
+    }
+}
+
+pub struct LabelOpts {
+    pub db_path: String,
+    pub placement_id: i64,
+    pub label: String,
+}
+
+impl Default for LabelOpts {
+    fn default() -> Self {
+        Self { db_path: DEFAULT_DB_PATH.to_string(), placement_id: 0, label: String::new() }
+    }
+}
+
+pub struct PublishOpts {
+    pub db_path: String,
+    pub placement_id: i64,
+}
+
+impl Default for PublishOpts {
+    fn default() -> Self {
+        Self { db_path: DEFAULT_DB_PATH.to_string(), placement_id: 0 }
+    }
+}
+
+pub struct ShowOpts {
+    pub db_path: String,
+    pub departments: Vec<String>,
+    pub placement_id: i64,
+}
+
+impl Default for ShowOpts {
+    fn default() -> Self {
+        Self { db_path: DEFAULT_DB_PATH.to_string(), departments: Vec::new(), placement_id: 0 }
+    }
+}
+
+pub struct PruneOpts {
+    pub db_path: String,
+    pub keep: usize,
+    pub dry_run: bool,
+}
+
+impl Default for PruneOpts {
+    fn default() -> Self {
+        Self { db_path: DEFAULT_DB_PATH.to_string(), keep: 1, dry_run: false }
+    }
+}
+
+pub struct DumpOpts {
+    pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub format: String,
+    pub balance_faculty_preferences: bool,
+    pub show_faculty_preference_priorities: bool,
+}
+
+impl Default for DumpOpts {
+    fn default() -> Self {
+        Self {
+            db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            format: "text".to_string(),
+            balance_faculty_preferences: true,
+            show_faculty_preference_priorities: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TweakSpec {
+    pub section: String,
+    pub room: String,
+    pub time_slot: String,
+}
+
+pub struct TweakOpts {
+    pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub starting_id: i64,
+    pub tweaks: Vec<TweakSpec>,
+    pub balance_faculty_preferences: bool,
+    pub show_faculty_preference_priorities: bool,
+}
+
+pub struct DfsOpts {
+    pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub pins: Vec<TweakSpec>,
+    pub starting_id: i64,
+    pub dfs_depth: usize,
+    pub repeat: bool,
+    pub balance_faculty_preferences: bool,
+    pub show_faculty_preference_priorities: bool,
+}
+
+pub struct LnsOpts {
+    pub db_path: String,
+    pub departments: Vec<String>,
+    pub background_id: i64,
+    pub baseline_id: i64,
+    pub baseline_priority: usize,
+    pub pins: Vec<TweakSpec>,
+    pub starting_id: i64,
+    pub solve_seconds: u64,
+    pub iterations: u64,
+    pub update_seconds: u64,
+    pub size: usize,
+    pub search: SatSearch,
+    pub seed: u64,
+    pub balance_faculty_preferences: bool,
+    pub show_faculty_preference_priorities: bool,
+}
+
+impl Default for LnsOpts {
+    fn default() -> Self {
+        Self {
+            db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            pins: Vec::new(),
+            starting_id: 0,
+            solve_seconds: 10 * 60,
+            iterations: 0,
+            update_seconds: 5,
+            size: 12,
+            search: SatSearch::Linear,
+            seed: 0,
+            balance_faculty_preferences: true,
+            show_faculty_preference_priorities: false,
+        }
+    }
+}
 
 impl Default for TweakOpts {
     fn default() -> Self {
         Self {
             db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
             starting_id: 0,
             tweaks: Vec::new(),
             balance_faculty_preferences: true,
@@ -470,6 +1421,11 @@ impl Default for DfsOpts {
     fn default() -> Self {
         Self {
             db_path: DEFAULT_DB_PATH.to_string(),
+            departments: Vec::new(),
+            background_id: 0,
+            baseline_id: 0,
+            baseline_priority: 5,
+            pins: Vec::new(),
             starting_id: 0,
             dfs_depth: 4,
             repeat: true,
@@ -479,9 +1435,22 @@ impl Default for DfsOpts {
     }
 }
 
-trait HasFacultyPreferencePriorityPolicy {
+trait CommonOpts {
+    fn db_path(&self) -> &str;
+    fn departments(&self) -> &[String];
     fn balance_faculty_preferences(&self) -> bool;
     fn show_faculty_preference_priorities(&self) -> bool;
+    fn background_id(&self) -> i64;
+    fn baseline_id(&self) -> i64;
+    fn baseline_priority(&self) -> usize;
+
+    fn background(&self) -> Option<i64> {
+        if self.background_id() > 0 { Some(self.background_id()) } else { None }
+    }
+
+    fn baseline(&self) -> Option<(i64, usize)> {
+        if self.baseline_id() > 0 { Some((self.baseline_id(), self.baseline_priority())) } else { None }
+    }
 
     fn faculty_preference_priority_policy(&self) -> FacultyPreferencePriorityPolicy {
         if self.balance_faculty_preferences() {
@@ -490,12 +1459,149 @@ trait HasFacultyPreferencePriorityPolicy {
             FacultyPreferencePriorityPolicy::Stated
         }
     }
+
+    // the options that select the input, as command-line flags
+    fn common_options(&self) -> Vec<String> {
+        let mut options = vec![format!("--db-path {}", shell_quote(self.db_path()))];
+        for department in self.departments() {
+            options.push(format!("--department {}", shell_quote(department)));
+        }
+        if let Some(id) = self.background() {
+            options.push(format!("--background {}", id));
+        }
+        if let Some((id, priority)) = self.baseline() {
+            options.push(format!("--baseline {} --baseline-priority {}", id, priority));
+        }
+        options.push(format!("--balance-faculty-preferences {}", self.balance_faculty_preferences()));
+        options
+    }
+}
+
+// the full option sets recorded with the placements each command saves
+
+impl GenOpts {
+    fn options(&self) -> String {
+        let mut options = self.common_options();
+        options.extend(pin_options("--pin", &self.pins));
+        options.push(format!("--warmup {}", sec_to_string(self.warmup_seconds)));
+        options.push(format!("--id {}", self.starting_id));
+        options.push(format!("--time {}", sec_to_string(self.solve_seconds)));
+        options.push(format!("--rehome-global {}", sec_to_string(self.rehome_global_seconds)));
+        options.push(format!("--rehome-local {}", sec_to_string(self.rehome_local_seconds)));
+        options.push(format!("--update {}", sec_to_string(self.update_seconds)));
+        options.push(format!("--bias-min {}", self.bias_min));
+        options.push(format!("--bias-max {}", self.bias_max));
+        options.push(format!("--bias-step {}", self.bias_step));
+        options.push(format!("--dfs-depth {}", self.dfs_depth));
+        options.push(format!("--fallback {}", self.fallback));
+        options.push(format!("--threads {}", self.threads));
+        options.push(format!("--strategy {}", self.strategy.name()));
+        match self.strategy {
+            SearchStrategy::Walk => {}
+            SearchStrategy::Anneal => options.push(format!("--anneal-final {}", self.anneal_final)),
+            SearchStrategy::LateAcceptance => options.push(format!("--late-length {}", self.late_length)),
+        }
+        options.push(format!("--seed {}", self.seed));
+        options.push(format!("--iterations {}", self.iterations));
+        options.push(format!("--warmup-tries {}", self.warmup_tries));
+        options.push(format!("--stall {}", self.stall));
+        if let Some(target) = self.target {
+            options.push(format!("--target {}", shell_quote(&target.to_string())));
+        }
+        options.push(format!("--stop-at-optimum {}", self.stop_at_optimum));
+        options.push(format!("--prove-bound {}", self.prove_bound));
+        options.join(" ")
+    }
+}
+
+impl SatOpts {
+    fn options(&self) -> String {
+        let mut options = self.common_options();
+        options.extend(pin_options("--pin", &self.pins));
+        options.push(format!("--search {}", self.search.name()));
+        options.join(" ")
+    }
+}
+
+impl DfsOpts {
+    fn options(&self) -> String {
+        let mut options = self.common_options();
+        options.extend(pin_options("--pin", &self.pins));
+        options.push(format!("--id {}", self.starting_id));
+        options.push(format!("--dfs-depth {}", self.dfs_depth));
+        options.push(format!("--repeat {}", self.repeat));
+        options.join(" ")
+    }
+}
+
+impl LnsOpts {
+    fn options(&self) -> String {
+        let mut options = self.common_options();
+        options.extend(pin_options("--pin", &self.pins));
+        options.push(format!("--id {}", self.starting_id));
+        options.push(format!("--time {}", sec_to_string(self.solve_seconds)));
+        options.push(format!("--iterations {}", self.iterations));
+        options.push(format!("--update {}", sec_to_string(self.update_seconds)));
+        options.push(format!("--size {}", self.size));
+        options.push(format!("--search {}", self.search.name()));
+        options.push(format!("--seed {}", self.seed));
+        options.join(" ")
+    }
+}
+
+impl ImportOpts {
+    fn options(&self) -> String {
+        let mut options = self.common_options();
+        options.push(format!("--file {}", shell_quote(&self.file)));
+        options.join(" ")
+    }
+}
+
+impl TweakOpts {
+    fn options(&self) -> String {
+        let mut options = self.common_options();
+        options.push(format!("--id {}", self.starting_id));
+        options.extend(pin_options("--tweak", &self.tweaks));
+        options.join(" ")
+    }
+}
+
+impl RepairOpts {
+    fn options(&self) -> String {
+        let mut options = self.common_options();
+        options.push(format!("--id {}", self.starting_id));
+        options.join(" ")
+    }
+}
+
+fn pin_options(flag: &str, specs: &[TweakSpec]) -> Vec<String> {
+    specs
+        .iter()
+        .map(|spec| format!("{} {}", flag, shell_quote(&format!("{},{},{}", spec.section, spec.room, spec.time_slot))))
+        .collect()
+}
+
+// quote a value so the recorded options can be pasted back into a shell
+fn shell_quote(s: &str) -> String {
+    if !s.is_empty() && s.chars().all(|ch| ch.is_ascii_alphanumeric() || "-_./,:+=".contains(ch)) {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
 }
 
-macro_rules! impl_preference_policy {
+macro_rules! impl_common_opts {
     ($($type:ty),+ $(,)?) => {
         $(
-            impl HasFacultyPreferencePriorityPolicy for $type {
+            impl CommonOpts for $type {
+                fn db_path(&self) -> &str {
+                    &self.db_path
+                }
+
+                fn departments(&self) -> &[String] {
+                    &self.departments
+                }
+
                 fn balance_faculty_preferences(&self) -> bool {
                     self.balance_faculty_preferences
                 }
@@ -503,12 +1609,39 @@ macro_rules! impl_preference_policy {
                 fn show_faculty_preference_priorities(&self) -> bool {
                     self.show_faculty_preference_priorities
                 }
+
+                fn background_id(&self) -> i64 {
+                    self.background_id
+                }
+
+                fn baseline_id(&self) -> i64 {
+                    self.baseline_id
+                }
+
+                fn baseline_priority(&self) -> usize {
+                    self.baseline_priority
+                }
             }
         )+
     };
 }
 
-impl_preference_policy!(GenOpts, SatOpts, DfsOpts, PrintOpts, DumpOpts, TweakOpts);
+impl_common_opts!(
+    GenOpts,
+    SatOpts,
+    DiagnoseOpts,
+    DfsOpts,
+    LnsOpts,
+    PrintOpts,
+    RepairOpts,
+    DiffOpts,
+    ImportOpts,
+    ExportOpts,
+    HtmlOpts,
+    IcalOpts,
+    DumpOpts,
+    TweakOpts
+);
 
 fn print_usage(command: Option<String>) {
     match command.as_deref() {
@@ -518,6 +1651,9 @@ fn print_usage(command: Option<String>) {
             eprintln!();
             eprintln!("Options:");
             eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            print_pin_usage();
             eprintln!(
                 "  -w, --warmup <duration>        Warmup period (default: {})",
                 sec_to_string(default.warmup_seconds)
@@ -547,6 +1683,62 @@ fn print_usage(command: Option<String>) {
                 "  -f, --fallback <bool>          Rehome to fallback instead of new warmup (default: {})",
                 default.fallback
             );
+            eprintln!(
+                "  -j, --threads <int>            Searches to run in parallel, 0 for one per core (default: {})",
+                default.threads
+            );
+            eprintln!(
+                "      --strategy <name>          Search strategy: walk, anneal, or late (default: {})",
+                default.strategy.name()
+            );
+            eprintln!(
+                "      --anneal-final <float>     Final annealing temperature as a fraction of the start (default: {})",
+                default.anneal_final
+            );
+            eprintln!(
+                "      --late-length <int>        Late-acceptance history length in moves (default: {})",
+                default.late_length
+            );
+            eprintln!("      --seed <int>               Random seed, 0 to pick one (default: {})", default.seed);
+            eprintln!(
+                "      --iterations <int>         Big steps (or moves) to take instead of running for --time (default: {})",
+                default.iterations
+            );
+            eprintln!(
+                "      --warmup-tries <int>       Schedules to try instead of warming up for --warmup (default: {})",
+                default.warmup_tries
+            );
+            eprintln!(
+                "      --stall <int>              Stop after this many big steps (or moves) without a new best (default: {})",
+                default.stall
+            );
+            eprintln!("      --target <score>           Stop once the best score is this good through its last level,");
+            eprintln!("                                 e.g., \"9x0\" for zero through level 9 or \"<15x3,17x2>\"");
+            eprintln!(
+                "      --stop-at-optimum <bool>   Stop on reaching the SAT-proven optimum (default: {})",
+                default.stop_at_optimum
+            );
+            eprintln!(
+                "      --prove-bound <bool>       Prove the optimum level by level with SAT during the search (default: {})",
+                default.prove_bound
+            );
+            eprintln!();
+            eprintln!("The rehoming, bias, DFS, and fallback options apply to the walk. The anneal and late");
+            eprintln!("strategies make single-section moves, and count moves instead of big steps.");
+            eprintln!("With more than one thread, --stall ends each search on its own, the warmup is split");
+            eprintln!("across the threads and each search starts from its own thread's warmup schedule (or");
+            eprintln!("all from --id), and a search that trails another for --rehome-global moves onto the");
+            eprintln!("other's best schedule.");
+            eprintln!();
+            eprintln!("A single-threaded run with a fixed --seed, --iterations, and --warmup-tries (or a");
+            eprintln!("starting --id) is reproducible.");
+            eprintln!();
+            eprintln!("Status lines show the proven lower bound, the last level it covers, and the first");
+            eprintln!("level where the best score is above it. The bound comes from the starting schedule");
+            eprintln!("and, with --prove-bound, from a SAT search that runs on one extra core on top of");
+            eprintln!("--threads, so the search ends early once the best score meets the bound through");
+            eprintln!("every level. When that happens depends on how fast the proof goes, so runs that");
+            eprintln!("need to be reproducible should leave --prove-bound off.");
             print_preference_balance_usage(default.balance_faculty_preferences);
         }
 
@@ -556,6 +1748,25 @@ fn print_usage(command: Option<String>) {
             eprintln!();
             eprintln!("Options:");
             eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            print_pin_usage();
+            eprintln!(
+                "  -s, --search <mode>            Violation search: linear, bisect, or core (default: {})",
+                default.search.name()
+            );
+            print_preference_balance_usage(default.balance_faculty_preferences);
+        }
+
+        Some("diagnose") => {
+            let default = DiagnoseOpts::default();
+            eprintln!("Usage: marmot diagnose [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            print_pin_usage();
             print_preference_balance_usage(default.balance_faculty_preferences);
         }
 
@@ -565,31 +1776,229 @@ fn print_usage(command: Option<String>) {
             eprintln!();
             eprintln!("Options:");
             eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            print_pin_usage();
             eprintln!("  -i, --id <int>                 ID of schedule to start from (0 to use best in DB)");
             eprintln!("  -p, --dfs-depth <int>          DFS depth (default: {})", default.dfs_depth);
             eprintln!("  -r, --repeat <bool>            Repeat automatically on success (default: {})", default.repeat);
             print_preference_balance_usage(default.balance_faculty_preferences);
         }
 
+        Some("lns") => {
+            let default = LnsOpts::default();
+            eprintln!("Usage: marmot lns [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            print_pin_usage();
+            eprintln!("  -i, --id <int>                 ID of schedule to start from (0 to use best in DB)");
+            eprintln!(
+                "  -t, --time <duration>          Total time (default: {})",
+                sec_to_string(default.solve_seconds)
+            );
+            eprintln!(
+                "      --iterations <int>         Neighborhoods to solve instead of running for --time (default: {})",
+                default.iterations
+            );
+            eprintln!(
+                "  -u, --update <duration>        Status update interval (default: {})",
+                sec_to_string(default.update_seconds)
+            );
+            eprintln!(
+                "      --size <int>               Sections to free in a neighbors neighborhood (default: {})",
+                default.size
+            );
+            eprintln!(
+                "  -s, --search <mode>            Violation search: linear, bisect, or core (default: {})",
+                default.search.name()
+            );
+            eprintln!("      --seed <int>               Random seed, 0 to pick one (default: {})", default.seed);
+            eprintln!();
+            eprintln!("Each iteration frees the sections of one faculty member, one room, one time band, or a");
+            eprintln!("cluster of related sections, fixes every other section in place, and asks the SAT");
+            eprintln!("solver for the best way to place the freed sections.");
+            print_preference_balance_usage(default.balance_faculty_preferences);
+        }
+
         Some("print") => {
             let default = PrintOpts::default();
             eprintln!("Usage: marmot print [options]");
             eprintln!();
             eprintln!("Options:");
             eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
             eprintln!(
                 "  -i, --id <int>                 ID of schedule to use (0 to use best in DB, default: {})",
                 default.starting_id
             );
+            eprintln!("      --format <text|json>       Output format (default: {})", default.format);
             print_preference_balance_usage(default.balance_faculty_preferences);
         }
 
+        Some("repair") => {
+            let default = RepairOpts::default();
+            eprintln!("Usage: marmot repair [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            eprintln!(
+                "  -i, --id <int>                 ID of schedule to repair (0 to use best in DB, default: {})",
+                default.starting_id
+            );
+            print_preference_balance_usage(default.balance_faculty_preferences);
+            eprintln!();
+            eprintln!("Places new sections and sections whose saved placement the input no longer allows,");
+            eprintln!("without moving anything else, and saves the result as a new placement.");
+        }
+
+        Some("diff") => {
+            let default = DiffOpts::default();
+            eprintln!("Usage: marmot diff [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            eprintln!("  -a, --old <int>                ID of the schedule to compare from");
+            eprintln!("  -b, --new <int>                ID of the schedule to compare to");
+            eprintln!("  -f, --faculty <name>           Only show changes involving this faculty member");
+            print_preference_balance_usage(default.balance_faculty_preferences);
+        }
+
+        Some("import") => {
+            let default = ImportOpts::default();
+            eprintln!("Usage: marmot import [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            eprintln!("  -f, --file <path>              Registrar CSV export to import (sheet or class search format)");
+            print_preference_balance_usage(default.balance_faculty_preferences);
+        }
+
+        Some("export") => {
+            let default = ExportOpts::default();
+            eprintln!("Usage: marmot export [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            eprintln!(
+                "  -i, --id <int>                 ID of schedule to export (0 to use best in DB, default: {})",
+                default.starting_id
+            );
+            eprintln!("  -t, --template <path>          Registrar CSV to take CRNs and other columns from");
+            eprintln!("  -o, --output <path>            Where to write the updated registrar CSV");
+            print_preference_balance_usage(default.balance_faculty_preferences);
+        }
+
+        Some("html") => {
+            let default = HtmlOpts::default();
+            eprintln!("Usage: marmot html [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            eprintln!(
+                "  -i, --id <int>                 ID of schedule to show (0 to use best in DB, default: {})",
+                default.starting_id
+            );
+            eprintln!("  -o, --output <path>            Where to write the HTML report (default: {})", default.output);
+            eprintln!("      --data <path>              Also write the viewer's data, e.g. ui/placement.js");
+            print_preference_balance_usage(default.balance_faculty_preferences);
+        }
+
+        Some("ical") => {
+            let default = IcalOpts::default();
+            eprintln!("Usage: marmot ical [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            eprintln!(
+                "  -i, --id <int>                 ID of schedule to export (0 to use best in DB, default: {})",
+                default.starting_id
+            );
+            eprintln!(
+                "  -o, --output <path>            Directory for faculty/ and rooms/ calendars (default: {})",
+                default.output
+            );
+            print_preference_balance_usage(default.balance_faculty_preferences);
+        }
+
+        Some("list") => {
+            let default = ListOpts::default();
+            eprintln!("Usage: marmot list [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+        }
+
+        Some("label") => {
+            let default = LabelOpts::default();
+            eprintln!("Usage: marmot label [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            eprintln!("  -i, --id <int>                 ID of the placement to label");
+            eprintln!("  -n, --name <text>              Label to give it (empty to clear the label)");
+        }
+
+        Some("publish") => {
+            let default = PublishOpts::default();
+            eprintln!("Usage: marmot publish [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            eprintln!("  -i, --id <int>                 ID of the placement to publish as the official draft");
+        }
+
+        Some("show") => {
+            let default = ShowOpts::default();
+            eprintln!("Usage: marmot show [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            eprintln!("      --department <name>        Department scope of the published schedule (repeatable)");
+            eprintln!(
+                "  -i, --id <int>                 ID of schedule to show (0 for the published one, default: {})",
+                default.placement_id
+            );
+            eprintln!();
+            eprintln!("Shows how a schedule was produced and the schedules it was derived from.");
+        }
+
+        Some("prune") => {
+            let default = PruneOpts::default();
+            eprintln!("Usage: marmot prune [options]");
+            eprintln!();
+            eprintln!("Options:");
+            eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            eprintln!("  -k, --keep <int>               Best placements to keep per scope (default: {})", default.keep);
+            eprintln!("  -n, --dry-run <bool>           List what would be deleted (default: {})", default.dry_run);
+            eprintln!();
+            eprintln!("Labeled, published, and background/baseline placements are never deleted.");
+        }
+
         Some("dump") => {
             let default = DumpOpts::default();
             eprintln!("Usage: marmot dump [options]");
             eprintln!();
             eprintln!("Options:");
             eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
+            eprintln!("      --format <text|json>       Output format (default: {})", default.format);
             print_preference_balance_usage(default.balance_faculty_preferences);
         }
 
@@ -599,6 +2008,8 @@ fn print_usage(command: Option<String>) {
             eprintln!();
             eprintln!("Options:");
             eprintln!("  -d, --db-path <path>           Database path (default: {})", default.db_path);
+            print_department_usage();
+            print_baseline_usage(default.baseline_priority);
             eprintln!("  -i, --id <int>                 ID of schedule to start from (0 to use best in DB)");
             eprintln!("  -t, --tweak <section,room,time> Move a section to specified room and time (repeatable)");
             print_preference_balance_usage(default.balance_faculty_preferences);
@@ -613,8 +2024,21 @@ fn print_usage(command: Option<String>) {
             eprintln!("Commands:");
             eprintln!("  gen        Generate a new schedule from scratch");
             eprintln!("  sat        Generate a new schedule using SAT");
+            eprintln!("  diagnose   Explain why the hard constraints cannot all be met");
             eprintln!("  dfs        Try to improve a schedule using bounded DFS");
+            eprintln!("  lns        Try to improve a schedule by re-solving parts of it with SAT");
             eprintln!("  print      Print a schedule to the console");
+            eprintln!("  repair     Re-place sections a change to the input has invalidated");
+            eprintln!("  diff       Compare two schedules");
+            eprintln!("  import     Import and score a schedule from a registrar CSV export");
+            eprintln!("  export     Write a schedule into a registrar CSV template");
+            eprintln!("  html       Write a schedule as a standalone HTML report");
+            eprintln!("  ical       Write faculty and room calendars for a schedule");
+            eprintln!("  list       List saved schedules");
+            eprintln!("  label      Name a saved schedule");
+            eprintln!("  publish    Mark a saved schedule as the official draft");
+            eprintln!("  show       Show how a saved schedule was produced");
+            eprintln!("  prune      Delete saved schedules that have been superseded");
             eprintln!("  dump       Dump the input data to the console");
             eprintln!("  tweak      Make manual adjustments to an existing schedule");
             eprintln!();
@@ -623,6 +2047,29 @@ fn print_usage(command: Option<String>) {
     }
 }
 
+fn check_format(format: &str) -> Result<()> {
+    if format != "text" && format != "json" {
+        return Err(format!("Error: unknown output format \"{}\" (expected text or json)", format).into());
+    }
+    Ok(())
+}
+
+fn print_department_usage() {
+    eprintln!("      --department <name>        Only schedule this department (repeatable, default: all)");
+    eprintln!("      --background <int>         ID of placement to freeze other departments' sections from");
+}
+
+fn print_baseline_usage(default_priority: usize) {
+    eprintln!("      --baseline <int>           ID of published placement to move as few sections from as possible");
+    eprintln!(
+        "      --baseline-priority <int>  Priority of each section moved from the baseline (default: {default_priority})"
+    );
+}
+
+fn print_pin_usage() {
+    eprintln!("      --pin <section,room,time>  Keep a section at this room and time (repeatable)");
+}
+
 fn print_preference_balance_usage(default: bool) {
     eprintln!("      --balance-faculty-preferences <bool>  Entropy-balance faculty priorities (default: {default})");
     eprintln!(
@@ -632,7 +2079,7 @@ fn print_preference_balance_usage(default: bool) {
 
 struct CliParser {
     command: String,
-    pairs: HashMap<String, String>,
+    pairs: Vec<(String, String)>,
 }
 
 impl CliParser {
@@ -648,27 +2095,27 @@ impl CliParser {
         if args.len() % 2 == 1 {
             return Err(format!("Error: 'marmot {}' options must each have a value, e.g., -t 30m", command).into());
         }
-        let mut pairs = HashMap::new();
+        let mut pairs = Vec::new();
         for pair in args[2..].chunks_exact(2) {
-            pairs.insert(pair[0].clone(), pair[1].clone());
+            pairs.push((pair[0].clone(), pair[1].clone()));
         }
 
         Ok(CliParser { command: args[1].clone(), pairs })
     }
 
     fn leftover(&self) -> Result<()> {
-        // form an error based on the first unprocessed option we happen to find
-        if let Some((key, val)) = self.pairs.iter().next() {
-            return Err(format!("Error: 'marmot {}' with unknown option: {} {}", self.command, key, val).into());
+        // form an error based on the first unprocessed option
+        if let Some((key, val)) = self.pairs.first() {
+            return Err(
+                format!("Error: 'marmot {}' with unknown or repeated option: {} {}", self.command, key, val).into()
+            );
         }
         Ok(())
     }
 
     fn pair(&mut self, short: &str, long: &str) -> Option<(String, String)> {
-        self.pairs
-            .remove(short)
-            .map(|val| (short.to_string(), val))
-            .or_else(|| self.pairs.remove(long).map(|val| (long.to_string(), val)))
+        let index = self.pairs.iter().position(|(key, _)| key == short || key == long)?;
+        Some(self.pairs.remove(index))
     }
 
     fn string(&mut self, short: &str, long: &str, s: &mut String) -> Result<()> {
@@ -679,6 +2126,14 @@ impl CliParser {
         Ok(())
     }
 
+    fn strings(&mut self, short: &str, long: &str, list: &mut Vec<String>) -> Result<()> {
+        while let Some((_, val)) = self.pair(short, long) {
+            list.push(val);
+        }
+
+        Ok(())
+    }
+
     fn duration(&mut self, short: &str, long: &str, seconds: &mut u64) -> Result<()> {
         if let Some((key, val)) = self.pair(short, long) {
             match string_to_sec(val.as_str()) {
@@ -723,6 +2178,17 @@ impl CliParser {
         Ok(())
     }
 
+    fn uint64(&mut self, short: &str, long: &str, target: &mut u64) -> Result<()> {
+        if let Some((key, val)) = self.pair(short, long) {
+            match val.parse() {
+                Ok(n) => *target = n,
+                Err(msg) => return Err(format!("Error parsing option {}: {}", key, msg).into()),
+            }
+        }
+
+        Ok(())
+    }
+
     fn boolean(&mut self, short: &str, long: &str, target: &mut bool) -> Result<()> {
         if let Some((key, val)) = self.pair(short, long) {
             match val.parse() {
@@ -734,6 +2200,28 @@ impl CliParser {
         Ok(())
     }
 
+    fn sat_search(&mut self, short: &str, long: &str, target: &mut SatSearch) -> Result<()> {
+        if let Some((key, val)) = self.pair(short, long) {
+            match val.parse() {
+                Ok(mode) => *target = mode,
+                Err(msg) => return Err(format!("Error parsing option {}: {}", key, msg).into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn strategy(&mut self, short: &str, long: &str, target: &mut SearchStrategy) -> Result<()> {
+        if let Some((key, val)) = self.pair(short, long) {
+            match val.parse() {
+                Ok(strategy) => *target = strategy,
+                Err(msg) => return Err(format!("Error parsing option {}: {}", key, msg).into()),
+            }
+        }
+
+        Ok(())
+    }
+
     fn tweak_specs(&mut self, short: &str, long: &str, tweaks: &mut Vec<TweakSpec>) -> Result<bool> {
         if let Some((key, val)) = self.pair(short, long) {
             let parts: Vec<&str> = val.split(',').collect();
@@ -0,0 +1,379 @@
+use super::error::{Result, err};
+use super::print::ms_to_string;
+use sqlite::{Connection, OpenFlags, State};
+
+// saved placements accumulate with every warmup, SAT level, and random-walk
+// improvement; these commands browse, name, and trim that history
+
+pub fn list_placements(db_path: &str) -> Result<()> {
+    let db = Connection::open_with_flags(db_path, OpenFlags::new().with_read_only().with_no_mutex())?;
+    db.execute("PRAGMA foreign_keys = ON")?;
+
+    // a saved section is stale when the current input no longer allows its time slot or room
+    let mut stmt = db.prepare(
+        "WITH available_time_slots AS MATERIALIZED (
+                SELECT DISTINCT section, time_slot FROM time_slots_available_to_sections
+            ), available_rooms AS MATERIALIZED (
+                SELECT DISTINCT section, room FROM rooms_available_to_sections
+            ), stale AS (
+                SELECT placement_id, COUNT(1) AS stale_count
+                FROM placement_sections AS saved
+                WHERE NOT EXISTS (
+                        SELECT 1 FROM available_time_slots AS available
+                        WHERE available.section = saved.section AND available.time_slot = saved.time_slot)
+                    OR saved.room IS NOT NULL AND NOT EXISTS (
+                        SELECT 1 FROM available_rooms AS available
+                        WHERE available.section = saved.section AND available.room = saved.room)
+                GROUP BY placement_id
+            ), counts AS (
+                SELECT placement_id, COUNT(1) AS section_count
+                FROM placement_sections
+                GROUP BY placement_id
+            )
+        SELECT placement_id, score, comment, faculty_preference_priority_policy, department_scope,
+            background_placement_id, baseline_placement_id, created_at, modified_at,
+            COALESCE(section_count, 0), COALESCE(stale_count, 0), label, published
+        FROM placements
+        NATURAL LEFT JOIN counts
+        NATURAL LEFT JOIN stale
+        ORDER BY placement_id",
+    )?;
+
+    let mut rows = Vec::new();
+    while stmt.next()? == State::Row {
+        let id: i64 = stmt.read(0)?;
+        let score: String = stmt.read(1)?;
+        let comment: String = stmt.read(2)?;
+        let policy: String = stmt.read(3)?;
+        let scope: String = stmt.read(4)?;
+        let background: Option<i64> = stmt.read(5)?;
+        let baseline: Option<i64> = stmt.read(6)?;
+        let created_at: String = stmt.read(7)?;
+        let modified_at: String = stmt.read(8)?;
+        let sections: i64 = stmt.read(9)?;
+        let stale: i64 = stmt.read(10)?;
+        let label: Option<String> = stmt.read(11)?;
+        let published: i64 = stmt.read(12)?;
+
+        let mut scope = if scope.is_empty() { "all".to_string() } else { scope };
+        if let Some(id) = background {
+            scope = format!("{} bg:{}", scope, id);
+        }
+        if let Some(id) = baseline {
+            scope = format!("{} base:{}", scope, id);
+        }
+        let mut notes = Vec::new();
+        if published != 0 {
+            notes.push("published".to_string());
+        }
+        if let Some(label) = label {
+            notes.push(format!("label \"{}\"", label));
+        }
+        if stale > 0 {
+            notes.push(format!("{} stale", stale));
+        }
+        let modified = if modified_at == created_at { String::new() } else { modified_at };
+        rows.push([
+            id.to_string(),
+            score,
+            sections.to_string(),
+            policy,
+            scope,
+            created_at,
+            modified,
+            notes.join(", "),
+            comment,
+        ]);
+    }
+    if rows.is_empty() {
+        println!("no placements saved");
+        return Ok(());
+    }
+
+    let header = ["id", "score", "sections", "policy", "scope", "created", "modified", "notes", "comment"];
+    let mut widths = header.map(|elt| elt.chars().count());
+    for row in &rows {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count());
+        }
+    }
+    for row in std::iter::once(header.map(String::from)).chain(rows) {
+        let mut line = String::new();
+        for (i, field) in row.iter().enumerate() {
+            if i == 0 || i == 2 {
+                line.push_str(&format!("{:>width$}  ", field, width = widths[i]));
+            } else {
+                line.push_str(&format!("{:width$}  ", field, width = widths[i]));
+            }
+        }
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+// name a placement, or clear its name with an empty label
+pub fn label_placement(db_path: &str, placement_id: i64, label: &str) -> Result<()> {
+    let db = Connection::open_with_flags(db_path, OpenFlags::new().with_read_write().with_full_mutex())?;
+    db.execute("PRAGMA foreign_keys = ON")?;
+    db.execute("PRAGMA busy_timeout = 10000")?;
+
+    if !label.is_empty() {
+        let mut stmt = db.prepare("SELECT placement_id FROM placements WHERE label = ? AND placement_id <> ?")?;
+        stmt.bind((1, label))?;
+        stmt.bind((2, placement_id))?;
+        if stmt.next()? == State::Row {
+            let other: i64 = stmt.read(0)?;
+            return err(format!("label \"{}\" is already used by placement {}", label, other));
+        }
+    }
+
+    let mut stmt = db.prepare("UPDATE placements SET label = NULLIF(?, '') WHERE placement_id = ? RETURNING 1")?;
+    stmt.bind((1, label))?;
+    stmt.bind((2, placement_id))?;
+    if stmt.next()? != State::Row {
+        return err(format!("placement {} not found", placement_id));
+    }
+    while stmt.next()? != State::Done {}
+
+    if label.is_empty() {
+        println!("cleared the label on placement {}", placement_id);
+    } else {
+        println!("labeled placement {} \"{}\"", placement_id, label);
+    }
+    Ok(())
+}
+
+// mark a placement as the official draft for its department scope
+pub fn publish_placement(db_path: &str, placement_id: i64) -> Result<()> {
+    let db = Connection::open_with_flags(db_path, OpenFlags::new().with_read_write().with_full_mutex())?;
+    db.execute("PRAGMA foreign_keys = ON")?;
+    db.execute("PRAGMA busy_timeout = 10000")?;
+    db.execute("BEGIN")?;
+
+    let mut stmt = db.prepare("SELECT department_scope FROM placements WHERE placement_id = ?")?;
+    stmt.bind((1, placement_id))?;
+    let scope: String = match stmt.next()? {
+        State::Row => stmt.read(0)?,
+        State::Done => {
+            db.execute("ROLLBACK")?;
+            return err(format!("placement {} not found", placement_id));
+        }
+    };
+    drop(stmt);
+
+    let mut stmt = db.prepare(
+        "UPDATE placements SET published = 0
+            WHERE department_scope = ? AND published AND placement_id <> ?
+            RETURNING placement_id",
+    )?;
+    stmt.bind((1, scope.as_str()))?;
+    stmt.bind((2, placement_id))?;
+    while stmt.next()? == State::Row {
+        let previous: i64 = stmt.read(0)?;
+        println!("placement {} is no longer published", previous);
+    }
+    drop(stmt);
+
+    let mut stmt = db.prepare("UPDATE placements SET published = 1 WHERE placement_id = ?")?;
+    stmt.bind((1, placement_id))?;
+    while stmt.next()? != State::Done {
+        // no return rows expected
+    }
+    drop(stmt);
+    db.execute("COMMIT")?;
+
+    let scope = if scope.is_empty() { "all departments".to_string() } else { scope };
+    println!("published placement {} for {}", placement_id, scope);
+    Ok(())
+}
+
+// show how a placement was produced, then the placement it was derived from,
+// and so on back to the start of its lineage; placement 0 means the published
+// placement for the given department scope
+pub fn show_placement(db_path: &str, placement_id: i64, scope: &str) -> Result<()> {
+    let db = Connection::open_with_flags(db_path, OpenFlags::new().with_read_only().with_no_mutex())?;
+    db.execute("PRAGMA foreign_keys = ON")?;
+
+    let mut next = Some(placement_id);
+    if placement_id == 0 {
+        let mut stmt = db.prepare("SELECT placement_id FROM placements WHERE department_scope = ? AND published")?;
+        stmt.bind((1, scope))?;
+        next = None;
+        while stmt.next()? == State::Row {
+            next = Some(stmt.read(0)?);
+        }
+        if next.is_none() {
+            let scope = if scope.is_empty() { "all departments" } else { scope };
+            return err(format!("no placement is published for {}", scope));
+        }
+    }
+
+    let mut seen = Vec::new();
+    while let Some(id) = next {
+        if seen.contains(&id) {
+            break;
+        }
+        seen.push(id);
+        let mut stmt = db.prepare(
+            "SELECT score, comment, label, published, command, options, seed, version, wall_ms,
+                parent_placement_id, created_at, modified_at
+            FROM placements
+            WHERE placement_id = ?",
+        )?;
+        stmt.bind((1, id))?;
+        if stmt.next()? != State::Row {
+            if seen.len() == 1 {
+                return err(format!("placement {} not found", id));
+            }
+            println!("placement {} has been deleted", id);
+            break;
+        }
+        let score: String = stmt.read(0)?;
+        let comment: String = stmt.read(1)?;
+        let label: Option<String> = stmt.read(2)?;
+        let published: i64 = stmt.read(3)?;
+        let command: String = stmt.read(4)?;
+        let options: String = stmt.read(5)?;
+        let seed: Option<i64> = stmt.read(6)?;
+        let version: String = stmt.read(7)?;
+        let wall_ms: Option<i64> = stmt.read(8)?;
+        let parent: Option<i64> = stmt.read(9)?;
+        let created_at: String = stmt.read(10)?;
+        let modified_at: String = stmt.read(11)?;
+
+        if seen.len() > 1 {
+            println!();
+            println!("derived from:");
+        }
+        let mut title = format!("placement {}", id);
+        if let Some(label) = label {
+            title = format!("{} \"{}\"", title, label);
+        }
+        if published != 0 {
+            title = format!("{} (published)", title);
+        }
+        println!("{}", title);
+        println!("    score:    {}", score);
+        println!("    comment:  {}", comment);
+        if command.is_empty() {
+            println!("    command:  not recorded");
+        } else {
+            println!("    command:  marmot {} {}", command, options);
+        }
+        if let Some(seed) = seed {
+            println!("    seed:     {}", seed as u64);
+        }
+        if !version.is_empty() {
+            println!("    version:  {}", version);
+        }
+        if let Some(ms) = wall_ms {
+            println!("    ran for:  {}", ms_to_string(ms as u128));
+        }
+        if modified_at == created_at {
+            println!("    created:  {}", created_at);
+        } else {
+            println!("    created:  {}, last updated {}", created_at, modified_at);
+        }
+        next = parent;
+    }
+    Ok(())
+}
+
+// delete placements outranked by at least `keep` others solved under the same
+// policy, department scope, background, and baseline, ranked the same way
+// load_schedule picks the best; labeled, published, and referenced placements
+// are always kept
+pub fn prune_placements(db_path: &str, keep: usize, dry_run: bool) -> Result<()> {
+    if keep == 0 {
+        return err("prune must keep at least one placement per scope");
+    }
+    let db = Connection::open_with_flags(db_path, OpenFlags::new().with_read_write().with_full_mutex())?;
+    db.execute("PRAGMA foreign_keys = ON")?;
+    db.execute("PRAGMA busy_timeout = 10000")?;
+    db.execute("BEGIN")?;
+
+    let mut stmt = db.prepare(
+        "WITH ranked AS (
+                SELECT placement_id, score, comment, label, published,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY faculty_preference_priority_policy, department_scope,
+                            background_placement_id, baseline_placement_id, baseline_priority
+                        ORDER BY sort_score, modified_at DESC) AS rank
+                FROM placements
+            )
+        SELECT placement_id, score, comment
+        FROM ranked
+        WHERE rank > ? AND label IS NULL AND NOT published
+            AND placement_id NOT IN (
+                SELECT background_placement_id FROM placements WHERE background_placement_id IS NOT NULL
+                UNION
+                SELECT baseline_placement_id FROM placements WHERE baseline_placement_id IS NOT NULL)
+        ORDER BY placement_id",
+    )?;
+    stmt.bind((1, keep as i64))?;
+    let mut doomed = Vec::new();
+    while stmt.next()? == State::Row {
+        let id: i64 = stmt.read(0)?;
+        let score: String = stmt.read(1)?;
+        let comment: String = stmt.read(2)?;
+        println!("{} placement {} {} {}", if dry_run { "would delete" } else { "deleting" }, id, score, comment);
+        doomed.push(id);
+    }
+    drop(stmt);
+
+    if dry_run {
+        db.execute("ROLLBACK")?;
+        println!("{} placements would be deleted", doomed.len());
+        return Ok(());
+    }
+    for &id in &doomed {
+        let mut stmt = db.prepare("DELETE FROM placements WHERE placement_id = ?")?;
+        stmt.bind((1, id))?;
+        while stmt.next()? != State::Done {
+            // no return rows expected
+        }
+    }
+    db.execute("COMMIT")?;
+    println!("deleted {} placements", doomed.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_keeps_the_best_and_anything_labeled_published_or_referenced() {
+        let path = std::env::temp_dir().join(format!("marmot-prune-{}.db", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        {
+            let db = Connection::open(path).unwrap();
+            db.execute(include_str!("../data/schema.sql")).unwrap();
+            db.execute(
+                "INSERT INTO placements (placement_id, score, sort_score, optimum_score_prefix, comment,
+                        created_at, modified_at, label, published, baseline_placement_id, baseline_priority)
+                    VALUES
+                    (1, '', '1', '', '', '', '', NULL, 0, NULL, NULL),
+                    (2, '', '2', '', '', '', '', NULL, 0, NULL, NULL),
+                    (3, '', '3', '', '', '', '', 'keep', 0, NULL, NULL),
+                    (4, '', '4', '', '', '', '', NULL, 1, NULL, NULL),
+                    (5, '', '5', '', '', '', '', NULL, 0, NULL, NULL),
+                    (6, '', '6', '', '', '', '', NULL, 0, 5, 5)",
+            )
+            .unwrap();
+        }
+        prune_placements(path, 1, false).unwrap();
+
+        let db = Connection::open(path).unwrap();
+        let mut stmt = db.prepare("SELECT placement_id FROM placements ORDER BY placement_id").unwrap();
+        let mut left = Vec::new();
+        while stmt.next().unwrap() == State::Row {
+            left.push(stmt.read::<i64, _>(0).unwrap());
+        }
+        assert_eq!(left, vec![1, 3, 4, 5, 6]);
+        drop(stmt);
+        drop(db);
+        let _ = std::fs::remove_file(path);
+    }
+}
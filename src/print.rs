@@ -3,6 +3,7 @@ use super::input::*;
 use super::score::*;
 use super::solver::*;
 use std::cmp::max;
+use std::collections::HashMap;
 
 pub fn print_schedule(input: &Input, schedule: &Schedule) {
     let mut rooms: Vec<usize> = schedule.placements.iter().filter_map(|Placement { room, .. }| *room).collect();
@@ -42,6 +43,7 @@ pub fn print_schedule(input: &Input, schedule: &Schedule) {
         let sec = &input.sections[section];
         let section_name = sec.name.clone();
         let faculty_name = match sec.faculty.len() {
+            _ if sec.background => "(frozen)".to_string(),
             0 => "".to_string(),
             1 => input.faculty[sec.faculty[0]].name.clone(),
             _ => format!("{}+", input.faculty[sec.faculty[0]].name.clone()),
@@ -79,16 +81,16 @@ pub fn print_schedule(input: &Input, schedule: &Schedule) {
     }
 }
 
-pub fn print_problems(input: &Input, schedule: &Schedule) {
+// every problem in a schedule as (priority, faculty involved, message)
+fn collect_problems(input: &Input, schedule: &Schedule) -> Vec<(u8, Vec<usize>, String)> {
     let mut lst = Vec::new();
     for (section, placement) in schedule.placements.iter().enumerate() {
         if placement.time_slot.is_none() {
             lst.push((
                 LEVEL_FOR_UNPLACED_SECTION,
-                String::new(),
+                input.sections[section].faculty.clone(),
                 format!("{} is not placed", input.sections[section].name),
             ));
-            continue;
         }
     }
     for penalty_list in &schedule.penalties {
@@ -102,14 +104,21 @@ pub fn print_problems(input: &Input, schedule: &Schedule) {
                 faculty.dedup();
             }
             let (priority, msg) = penalty.get_score_message(input, schedule);
+            lst.push((priority, faculty, msg));
+        }
+    }
+    lst
+}
 
-            // curriculum conflicts are displayed once, preferences are per-faculty
-            if faculty.is_empty() || priority < START_LEVEL_FOR_PREFERENCES {
-                lst.push((priority, String::new(), msg));
-            } else {
-                for elt in faculty {
-                    lst.push((priority, input.faculty[elt].name.clone(), msg.clone()));
-                }
+pub fn print_problems(input: &Input, schedule: &Schedule) {
+    let mut lst = Vec::new();
+    for (priority, faculty, msg) in collect_problems(input, schedule) {
+        // curriculum conflicts are displayed once, preferences are per-faculty
+        if faculty.is_empty() || priority < START_LEVEL_FOR_PREFERENCES {
+            lst.push((priority, String::new(), msg));
+        } else {
+            for elt in faculty {
+                lst.push((priority, input.faculty[elt].name.clone(), msg.clone()));
             }
         }
     }
@@ -129,6 +138,106 @@ pub fn print_problems(input: &Input, schedule: &Schedule) {
     }
 }
 
+// compare two schedules for the same input, optionally limited to one faculty member
+pub fn print_diff(input: &Input, old: &Schedule, new: &Schedule, faculty: Option<usize>) {
+    println!("score: {} -> {}", old.score, new.score);
+    for priority in 0..PRIORITY_LEVELS {
+        let (before, after) = (old.score.levels[priority], new.score.levels[priority]);
+        if before != after {
+            println!("    {:2}: {} -> {} ({:+})", priority, before, after, after - before);
+        }
+    }
+
+    let involved = |faculty_list: &[usize]| match faculty {
+        Some(faculty) => faculty_list.contains(&faculty),
+        None => true,
+    };
+
+    // room/time changes
+    let mut moved = Vec::new();
+    for (section, elt) in input.sections.iter().enumerate() {
+        if elt.background || !involved(&elt.faculty) {
+            continue;
+        }
+        let (a, b) = (&old.placements[section], &new.placements[section]);
+        if a.time_slot == b.time_slot && a.room == b.room {
+            continue;
+        }
+        let describe = |placement: &Placement| match placement.time_slot {
+            Some(time_slot) => input.describe_placement(time_slot, placement.room),
+            None => "(unplaced)".to_string(),
+        };
+        moved.push(format!("{}: {} -> {}", elt.name, describe(a), describe(b)));
+    }
+    println!();
+    println!("{} section{} moved", moved.len(), if moved.len() == 1 { "" } else { "s" });
+    for line in moved {
+        println!("    {}", line);
+    }
+
+    // penalties are matched by message, so identical messages cancel one for one
+    let mut counts: HashMap<(u8, String), isize> = HashMap::new();
+    for (priority, faculty_list, msg) in collect_problems(input, old) {
+        if involved(&faculty_list) {
+            *counts.entry((priority, msg)).or_default() -= 1;
+        }
+    }
+    for (priority, faculty_list, msg) in collect_problems(input, new) {
+        if involved(&faculty_list) {
+            *counts.entry((priority, msg)).or_default() += 1;
+        }
+    }
+    let mut changes: Vec<_> = counts.into_iter().filter(|(_, count)| *count != 0).collect();
+    changes.sort_unstable();
+    for (label, sign) in [("disappeared", -1), ("appeared", 1)] {
+        let lst: Vec<_> = changes.iter().filter(|(_, count)| count.signum() == sign).collect();
+        let total: usize = lst.iter().map(|(_, count)| count.unsigned_abs()).sum();
+        println!();
+        println!("{} penalt{} {}", total, if total == 1 { "y" } else { "ies" }, label);
+        for ((priority, msg), count) in lst {
+            for _ in 0..count.abs() {
+                println!("    {priority:2}: {msg}");
+            }
+        }
+    }
+}
+
+// list every section whose room or time differs from the baseline placement
+pub fn print_changes(input: &Input, schedule: &Schedule) {
+    let Some((baseline_id, _)) = input.baseline else {
+        return;
+    };
+    let mut changes = Vec::new();
+    for (section, elt) in input.sections.iter().enumerate() {
+        if elt.background {
+            continue;
+        }
+        let placement = &schedule.placements[section];
+        let current = placement.time_slot.map(|time_slot| (time_slot, placement.room));
+        if current == elt.baseline {
+            continue;
+        }
+        let from = match elt.baseline {
+            Some((time_slot, room)) => input.describe_placement(time_slot, room),
+            None => "(not in baseline)".to_string(),
+        };
+        let to = match current {
+            Some((time_slot, room)) => input.describe_placement(time_slot, room),
+            None => "(unplaced)".to_string(),
+        };
+        changes.push(format!("{}: {} -> {}", elt.name, from, to));
+    }
+    println!(
+        "{} section{} changed from baseline placement {}",
+        changes.len(),
+        if changes.len() == 1 { "" } else { "s" },
+        baseline_id
+    );
+    for change in changes {
+        println!("    {}", change);
+    }
+}
+
 pub fn dump_input(departments: &[String], input: &Input) {
     if departments.is_empty() {
         print!("{} for all departments: ", input.term_name);
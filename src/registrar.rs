@@ -0,0 +1,595 @@
+use super::error::{Result, err};
+use super::input::*;
+use super::solver::*;
+use std::collections::{BTreeSet, HashMap};
+
+// one meeting row from a registrar export, reduced to what placement needs
+//
+// two layouts are understood: the scheduling sheet (Beg Time/End Time as HHMM,
+// with Bldg and Rm columns) and the class search listing (Time as
+// "9:00 am-9:50 am", with Location as "SNOW 144")
+pub struct RegistrarRow {
+    pub line: usize,
+    pub subject: String,
+    pub course: String,
+    pub section: String,
+    pub days: String,
+    pub times: Option<(u16, u16)>,
+    pub room: Option<String>,
+    pub xlist: String,
+}
+
+impl RegistrarRow {
+    // e.g., "CS 1400-01", with the section number padded the way our sections are named
+    pub fn section_name(&self) -> String {
+        format!("{} {}-{:0>2}", self.subject, self.course, self.section)
+    }
+}
+
+// a registrar spreadsheet as read from disk, with every column preserved
+pub struct RegistrarSheet {
+    pub path: String,
+    pub header: Vec<String>,
+    pub records: Vec<(usize, Vec<String>)>,
+    columns: HashMap<String, usize>,
+}
+
+impl RegistrarSheet {
+    pub fn read(path: &str) -> Result<Self> {
+        let text = match std::fs::read(path) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(e) => return err(format!("unable to read {}: {}", path, e)),
+        };
+        Self::parse(path, &text)
+    }
+
+    fn parse(path: &str, text: &str) -> Result<Self> {
+        let text = text.trim_start_matches('\u{feff}');
+
+        // some exports are saved as tab-separated text with a .csv name
+        let first_line = text.lines().next().unwrap_or("");
+        let delimiter = if first_line.contains('\t') { '\t' } else { ',' };
+        let mut records = parse_csv(text, delimiter);
+        if records.is_empty() {
+            return err(format!("{} is empty", path));
+        }
+        let (_, header) = records.remove(0);
+        let columns = header.iter().enumerate().map(|(i, name)| (clean(name).to_lowercase(), i)).collect();
+        Ok(RegistrarSheet { path: path.to_string(), header, records, columns })
+    }
+
+    pub fn has_column(&self, name: &str) -> bool {
+        self.columns.contains_key(name)
+    }
+
+    // look up a column by its case-insensitive header name
+    pub fn column(&self, name: &str) -> Result<usize> {
+        match self.columns.get(name) {
+            Some(&i) => Ok(i),
+            None => err(format!("{} has no {} column", self.path, name)),
+        }
+    }
+
+    // write in the layout the scheduling office receives: tab-separated with every field quoted
+    pub fn write(&self, path: &str) -> Result<()> {
+        let mut out = String::new();
+        for record in std::iter::once(&self.header).chain(self.records.iter().map(|(_, record)| record)) {
+            let fields: Vec<String> =
+                record.iter().map(|field| format!("\"{}\"", field.replace('"', "\"\""))).collect();
+            out.push_str(&fields.join("\t"));
+            out.push_str("\r\n");
+        }
+        if let Err(e) = std::fs::write(path, out) {
+            return err(format!("unable to write {}: {}", path, e));
+        }
+        Ok(())
+    }
+}
+
+pub fn read_registrar_csv(path: &str) -> Result<Vec<RegistrarRow>> {
+    let sheet = RegistrarSheet::read(path)?;
+    let subject = sheet.column("subject")?;
+    let course = sheet.column("course")?;
+    let section = sheet.column("section")?;
+    let days = sheet.column("days")?;
+    let xlist = sheet.columns.get("xlist").copied();
+    let is_sheet = sheet.has_column("beg time");
+
+    let mut rows = Vec::new();
+    for (line, record) in &sheet.records {
+        let field = |i: usize| record.get(i).map(|s| clean(s)).unwrap_or_default();
+        if field(subject).is_empty() {
+            continue;
+        }
+
+        let (times, room) = if is_sheet {
+            let start = parse_hhmm(&field(sheet.column("beg time")?));
+            let times = match (start, parse_hhmm(&field(sheet.column("end time")?))) {
+                (Some(start), Some(end)) if end > start => Some((start, end)),
+                _ => None,
+            };
+            let building = field(sheet.column("bldg")?);
+            let number = field(sheet.column("rm")?);
+            let room =
+                if building.is_empty() || number.is_empty() { None } else { Some(format!("{} {}", building, number)) };
+            (times, room)
+        } else {
+            let times = match field(sheet.column("time")?).split_once('-') {
+                Some((start, end)) => match (parse_clock(start), parse_clock(end)) {
+                    (Some(start), Some(end)) if end > start => Some((start, end)),
+                    _ => None,
+                },
+                None => None,
+            };
+            let location = field(sheet.column("location")?);
+            let room = if location.contains(' ') { Some(location) } else { None };
+            (times, room)
+        };
+
+        rows.push(RegistrarRow {
+            line: *line,
+            subject: field(subject),
+            course: field(course),
+            section: field(section),
+            days: field(days),
+            times,
+            room,
+            xlist: xlist.map(field).unwrap_or_default(),
+        });
+    }
+
+    Ok(rows)
+}
+
+// split CSV text into records, each with the line number where it starts;
+// quoted fields may contain delimiters, doubled quotes, and newlines
+fn parse_csv(text: &str, delimiter: char) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\n' {
+            line += 1;
+        }
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(ch),
+            }
+            continue;
+        }
+        match ch {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((start_line, std::mem::take(&mut record)));
+                start_line = line;
+            }
+            _ if ch == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(ch),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start_line, record));
+    }
+    records.retain(|(_, record)| record.iter().any(|field| !field.trim().is_empty()));
+    records
+}
+
+// spreadsheets write ="0900" to keep leading zeros
+fn clean(field: &str) -> String {
+    let field = field.trim();
+    let field = field.strip_prefix('=').unwrap_or(field);
+    field.trim_matches('"').trim().to_string()
+}
+
+// "0730" => 450 minutes
+fn parse_hhmm(s: &str) -> Option<u16> {
+    if s.len() != 4 || !s.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    let hours: u16 = s[..2].parse().ok()?;
+    let minutes: u16 = s[2..].parse().ok()?;
+    if hours < 24 && minutes < 60 { Some(hours * 60 + minutes) } else { None }
+}
+
+// "9:00 am" or "1:00 pm" => minutes since midnight
+fn parse_clock(s: &str) -> Option<u16> {
+    let s = s.trim().to_lowercase();
+    let (clock, pm) = if let Some(clock) = s.strip_suffix("pm") {
+        (clock.trim(), true)
+    } else {
+        (s.strip_suffix("am")?.trim(), false)
+    };
+    let (hours, minutes) = clock.split_once(':')?;
+    let hours: u16 = hours.parse().ok()?;
+    let minutes: u16 = minutes.parse().ok()?;
+    if hours == 0 || hours > 12 || minutes >= 60 {
+        return None;
+    }
+    Some((hours % 12 + if pm { 12 } else { 0 }) * 60 + minutes)
+}
+
+// place the sections of a registrar export into a new schedule, reporting
+// every row that could not be matched to a section, time slot, or room
+pub fn import_schedule(input: &Input, rows: &[RegistrarRow]) -> Schedule {
+    let mut schedule = Schedule::new(input);
+    let section_index: HashMap<&str, usize> =
+        input.sections.iter().enumerate().map(|(i, elt)| (elt.name.as_str(), i)).collect();
+    let room_index: HashMap<String, usize> =
+        input.rooms.iter().enumerate().map(|(i, elt)| (elt.name.to_lowercase(), i)).collect();
+
+    // rows for subjects we do not schedule at all are summarized rather than listed
+    let known_subjects: BTreeSet<&str> =
+        input.sections.iter().filter_map(|elt| elt.name.split_once(' ').map(|(subject, _)| subject)).collect();
+    let mut other_subjects = BTreeSet::new();
+    let mut other_rows = 0;
+
+    // a cross-listed meeting only needs to match one of its sections
+    let mut matched_xlists = BTreeSet::new();
+    for row in rows {
+        if !row.xlist.is_empty() && section_index.contains_key(row.section_name().as_str()) {
+            matched_xlists.insert(row.xlist.as_str());
+        }
+    }
+
+    let mut seen = vec![false; input.sections.len()];
+    for row in rows {
+        let name = row.section_name();
+        let Some(&section) = section_index.get(name.as_str()) else {
+            if !known_subjects.contains(row.subject.as_str()) {
+                other_subjects.insert(row.subject.as_str());
+                other_rows += 1;
+            } else if !matched_xlists.contains(row.xlist.as_str()) {
+                println!("line {}: {} does not match any section", row.line, name);
+            }
+            continue;
+        };
+        let elt = &input.sections[section];
+        if elt.background {
+            continue;
+        }
+        if seen[section] {
+            println!("line {}: ignoring an additional meeting for {}", row.line, name);
+            continue;
+        }
+        seen[section] = true;
+
+        let Some((start, end)) = row.times else {
+            println!("line {}: {} has no meeting time", row.line, name);
+            continue;
+        };
+        let Ok(days) = Days::parse(&row.days) else {
+            println!("line {}: {} meets on unknown days {}", row.line, name, row.days);
+            continue;
+        };
+        let time_name = format!("{}{:02}{:02}+{}", days, start / 60, start % 60, end - start);
+        let Some(time_slot) = input.time_slots.iter().position(|elt| {
+            elt.days.days == days.days && elt.start_time.minutes == start && elt.duration.minutes == end - start
+        }) else {
+            println!("line {}: {} meets at {}, which is off the time slot grid", row.line, name, time_name);
+            continue;
+        };
+        let room = match &row.room {
+            Some(room_name) => match room_index.get(&room_name.to_lowercase()) {
+                Some(&room) => Some(room),
+                None => {
+                    println!("line {}: {} is in {}, which is not a room being scheduled", row.line, name, room_name);
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        // pinned sections are already in place
+        if let Some(pin) = elt.pinned {
+            if pin != (time_slot, room) {
+                println!("line {}: {} is pinned elsewhere, keeping the pin", row.line, name);
+            }
+            continue;
+        }
+        // placements the section could never have are left for the solver to fill
+        if !elt.time_slots.iter().any(|option| option.time_slot == time_slot) {
+            println!("line {}: {} meets at {}, which is not one of its allowed times", row.line, name, time_name);
+            continue;
+        }
+        if !rooms_adapter(&elt.rooms).contains(&room) {
+            let room_name = room.map_or("no room".to_string(), |room| input.rooms[room].name.clone());
+            println!("line {}: {} is in {}, which is not one of its allowed rooms", row.line, name, room_name);
+            continue;
+        }
+        if schedule.has_hard_conflict(input, section, time_slot, &room, &[]).is_some() {
+            println!("line {}: {} has a hard conflict at {} and was left unplaced", row.line, name, time_name);
+            continue;
+        }
+        let _undo = move_section(input, &mut schedule, section, time_slot, &room);
+    }
+
+    if other_rows > 0 {
+        let subjects: Vec<&str> = other_subjects.into_iter().collect();
+        println!("skipped {} rows for subjects not being scheduled: {}", other_rows, subjects.join(", "));
+    }
+
+    // sections missing from the file are listed individually unless the file
+    // has no rows for that subject at all
+    let imported_subjects: BTreeSet<&str> = rows.iter().map(|row| row.subject.as_str()).collect();
+    let mut absent_subjects = BTreeSet::new();
+    let mut absent_sections = 0;
+    for (section, elt) in input.sections.iter().enumerate() {
+        if seen[section] || elt.background || elt.is_pinned() {
+            continue;
+        }
+        let subject = elt.name.split_once(' ').map_or(elt.name.as_str(), |(subject, _)| subject);
+        if imported_subjects.contains(subject) {
+            println!("{} is not in the import", elt.name);
+        } else {
+            absent_subjects.insert(subject);
+            absent_sections += 1;
+        }
+    }
+    if absent_sections > 0 {
+        let subjects: Vec<&str> = absent_subjects.into_iter().collect();
+        println!("{} sections left unplaced from subjects not in the import: {}", absent_sections, subjects.join(", "));
+    }
+
+    schedule
+}
+
+// the registrar fields for one placed section: days, begin, end, building, room number, capacity
+fn registrar_fields(input: &Input, placement: &Placement) -> Option<[String; 6]> {
+    let time_slot = &input.time_slots[placement.time_slot?];
+    let start = time_slot.start_time.minutes;
+    let end = start + time_slot.duration.minutes;
+    let (building, number, capacity) = match placement.room {
+        Some(room) => {
+            let room = &input.rooms[room];
+            let (building, number) = room.name.split_once(' ').unwrap_or((room.name.as_str(), ""));
+            (building.to_uppercase(), number.to_string(), Some(room.capacity.to_string()))
+        }
+        None => (String::new(), String::new(), None),
+    };
+    Some([
+        time_slot.days.to_string(),
+        format!("{:02}{:02}", start / 60, start % 60),
+        format!("{:02}{:02}", end / 60, end % 60),
+        building,
+        number,
+        capacity.unwrap_or_default(),
+    ])
+}
+
+// section suffixes that format-schedule never deletes (it marks them SKIP)
+const SKIP_SECTION_SUFFIXES: [char; 3] = ['S', 'J', 'X'];
+
+// fill a placement into a registrar template sheet
+//
+// rows for scheduled sections (and their cross-listed secondaries) get days,
+// times, room, and room capacity from the placement, sections missing from the
+// template are appended, and all other rows and columns pass through untouched;
+// a Change column marks each row as UPDATE, INSERT, or unchanged, and the way
+// format-schedule did, rows for scheduled courses whose sections are gone are
+// marked DELETE while rows the scheduler does not own are marked SKIP
+pub fn export_schedule(
+    input: &Input,
+    schedule: &Schedule,
+    sheet: &mut RegistrarSheet,
+    cross_listings: &HashMap<String, usize>,
+) -> Result<()> {
+    let subject = sheet.column("subject")?;
+    let course = sheet.column("course")?;
+    let section_column = sheet.column("section")?;
+    let columns = [
+        sheet.column("days")?,
+        sheet.column("beg time")?,
+        sheet.column("end time")?,
+        sheet.column("bldg")?,
+        sheet.column("rm")?,
+        sheet.column("seats cap")?,
+    ];
+    let xlist = sheet.columns.get("xlist").copied();
+    let crn = sheet.columns.get("crn").copied();
+    let width = sheet.header.len();
+    sheet.header.push("Change".to_string());
+
+    let section_index: HashMap<&str, usize> =
+        input.sections.iter().enumerate().map(|(i, elt)| (elt.name.as_str(), i)).collect();
+    let name_of = |record: &[String]| {
+        let field = |i: usize| record.get(i).map(|s| clean(s)).unwrap_or_default();
+        format!("{} {}-{:0>2}", field(subject), field(course), field(section_column))
+    };
+    let xlist_of = |record: &[String]| xlist.and_then(|i| record.get(i)).map(|s| clean(s)).unwrap_or_default();
+
+    // the courses being scheduled, e.g., ("CS", "1400")
+    let scheduled_courses: BTreeSet<(String, String)> = input
+        .sections
+        .iter()
+        .filter(|elt| !elt.background)
+        .filter_map(|elt| {
+            let (subject, rest) = elt.name.split_once(' ')?;
+            let (course, _) = rest.rsplit_once('-')?;
+            Some((subject.to_string(), course.to_string()))
+        })
+        .collect();
+
+    // rows in the same Xlist group as a scheduled section share its placement
+    let mut xlist_sections = HashMap::new();
+    for (_, record) in &sheet.records {
+        let group = xlist_of(record);
+        if let Some(&section) = section_index.get(name_of(record).as_str())
+            && !group.is_empty()
+        {
+            xlist_sections.insert(group, section);
+        }
+    }
+
+    let mut exported = vec![false; input.sections.len()];
+    let mut names_seen = BTreeSet::new();
+    let mut updated = 0;
+    let mut deleted = 0;
+    for (line, record) in &mut sheet.records {
+        record.resize(width, String::new());
+        let name = name_of(record);
+        let section = section_index
+            .get(name.as_str())
+            .or_else(|| cross_listings.get(&name))
+            .or_else(|| xlist_sections.get(&xlist_of(record)))
+            .copied();
+        let Some(section) = section.filter(|&section| !input.sections[section].background) else {
+            let course_key = (clean(&record[subject]), clean(&record[course]));
+            let in_scope = crn.is_none_or(|i| !clean(&record[i]).is_empty())
+                && !clean(&record[section_column]).ends_with(SKIP_SECTION_SUFFIXES)
+                && scheduled_courses.contains(&course_key);
+            if in_scope {
+                deleted += 1;
+            }
+            record.push(if in_scope { "DELETE" } else { "SKIP" }.to_string());
+            continue;
+        };
+        if !names_seen.insert(name.clone()) {
+            println!("line {}: leaving an additional meeting for {} unchanged", line, name);
+            record.push(String::new());
+            continue;
+        }
+        exported[section] = true;
+
+        let Some(fields) = registrar_fields(input, &schedule.placements[section]) else {
+            println!("line {}: {} is not placed, leaving it unchanged", line, name);
+            record.push(String::new());
+            continue;
+        };
+        let mut changed = false;
+        for (&i, value) in columns.iter().zip(fields) {
+            // sections without rooms keep whatever capacity the template had
+            if i == columns[5] && value.is_empty() {
+                continue;
+            }
+            if clean(&record[i]) != value {
+                record[i] = value;
+                changed = true;
+            }
+        }
+        if changed {
+            updated += 1;
+        }
+        record.push(if changed { "UPDATE".to_string() } else { String::new() });
+    }
+
+    let mut inserted = 0;
+    let mut skipped_subjects = BTreeSet::new();
+    let mut skipped = 0;
+    for (section, elt) in input.sections.iter().enumerate() {
+        if exported[section] || elt.background {
+            continue;
+        }
+        let Some(fields) = registrar_fields(input, &schedule.placements[section]) else {
+            skipped_subjects.insert(elt.name.split_once(' ').map_or(elt.name.as_str(), |(subject, _)| subject));
+            skipped += 1;
+            continue;
+        };
+        let Some((subject_name, rest)) = elt.name.split_once(' ') else {
+            return err(format!("unable to split section name {} into subject, course, and section", elt.name));
+        };
+        let Some((course_name, section_name)) = rest.rsplit_once('-') else {
+            return err(format!("unable to split section name {} into subject, course, and section", elt.name));
+        };
+        let mut record = vec![String::new(); width];
+        record[subject] = subject_name.to_string();
+        record[course] = course_name.to_string();
+        record[section_column] = section_name.to_string();
+        for (&i, value) in columns.iter().zip(fields) {
+            record[i] = value;
+        }
+        record.push("INSERT".to_string());
+        sheet.records.push((0, record));
+        inserted += 1;
+    }
+
+    if skipped > 0 {
+        let subjects: Vec<&str> = skipped_subjects.into_iter().collect();
+        println!("skipped {} unplaced sections that are not in the template: {}", skipped, subjects.join(", "));
+    }
+    println!("{} rows updated, {} rows inserted, {} rows deleted", updated, inserted, deleted);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::faculty_preferences::FacultyPreferencePriorityPolicy;
+
+    #[test]
+    fn parses_quoted_fields_and_both_time_formats() {
+        let records = parse_csv("a,\"b, \"\"c\"\"\"\n=\"0900\",\"x\ny\"\n", ',');
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].1, vec!["a".to_string(), "b, \"c\"".to_string()]);
+        assert_eq!(records[1].0, 2);
+        assert_eq!(clean(&records[1].1[0]), "0900");
+        assert_eq!(records[1].1[1], "x\ny");
+
+        assert_eq!(parse_hhmm("0730"), Some(450));
+        assert_eq!(parse_hhmm("TBA"), None);
+        assert_eq!(parse_clock("9:00 am"), Some(540));
+        assert_eq!(parse_clock("12:15 pm"), Some(735));
+        assert_eq!(parse_clock("1:00 pm"), Some(780));
+    }
+
+    #[test]
+    fn rows_for_vanished_sections_are_deleted_and_others_skipped() {
+        let input = Input {
+            term_name: "test".to_string(),
+            departments: vec![],
+            background_id: None,
+            baseline: None,
+            rooms: vec![Room { name: "SNOW 144".to_string(), capacity: 30 }],
+            time_slots: vec![TimeSlot {
+                name: "MWF0900+50".to_string(),
+                days: Days::parse("MWF").unwrap(),
+                start_time: Time::new(540),
+                duration: Duration::new(50),
+            }],
+            faculty: vec![],
+            sections: vec![Section {
+                name: "CS 1400-01".to_string(),
+                rooms: vec![RoomWithOptionalPriority { room: 0, priority: None }],
+                time_slots: vec![TimeSlotWithOptionalPriority { time_slot: 0, priority: None }],
+                faculty: vec![],
+                enrollment: None,
+                pinned: None,
+                baseline: None,
+                background: false,
+                hard_conflicts: vec![],
+                criteria: vec![],
+                neighbors: vec![],
+            }],
+            criteria: vec![],
+            faculty_preference_priority_policy: FacultyPreferencePriorityPolicy::Stated,
+            time_slot_conflicts: vec![vec![true]],
+        };
+        let mut schedule = Schedule::new(&input);
+        move_section(&input, &mut schedule, 0, 0, &Some(0));
+
+        let mut sheet = RegistrarSheet::parse(
+            "test.csv",
+            "CRN,Subject,Course,Section,Days,Beg Time,End Time,Bldg,Rm,Seats Cap\n\
+             100,CS,1400,01,MWF,0900,0950,SNOW,144,30\n\
+             101,CS,1400,02,TR,0900,1015,SNOW,144,30\n\
+             102,CS,1400,40J,TR,0900,1015,,,10\n\
+             ,CS,1400,03,TR,1030,1145,SNOW,144,30\n\
+             103,BIOL,1010,01,TR,0730,0845,SET,106,59\n",
+        )
+        .unwrap();
+        export_schedule(&input, &schedule, &mut sheet, &HashMap::new()).unwrap();
+        let changes: Vec<&str> = sheet.records.iter().map(|(_, record)| record.last().unwrap().as_str()).collect();
+        assert_eq!(changes, vec!["", "DELETE", "SKIP", "SKIP", "SKIP"]);
+    }
+}
@@ -25,6 +25,22 @@ pub enum SatCriterion {
         priority: u8,
     },
 
+    // A section placed in any of these rooms has too few or too many seats
+    RoomCapacity {
+        section: usize,
+        rooms: Vec<usize>,
+        too_small: bool,
+        priority: u8,
+    },
+
+    // A section that should stay where the baseline placement put it
+    SectionMoved {
+        section: usize,
+        time_slot: usize,
+        room: Option<usize>,
+        priority: u8,
+    },
+
     // A preference to avoid a specific time slot for a section
     TimeSlotPreference {
         section: usize,
@@ -112,6 +128,8 @@ impl SatCriterion {
             SatCriterion::Conflict { priority, .. } => *priority,
             SatCriterion::AntiConflict { priority, .. } => *priority,
             SatCriterion::RoomPreference { priority, .. } => *priority,
+            SatCriterion::RoomCapacity { priority, .. } => *priority,
+            SatCriterion::SectionMoved { priority, .. } => *priority,
             SatCriterion::TimeSlotPreference { priority, .. } => *priority,
             SatCriterion::FacultyDaysOff { priority, .. } => *priority,
             SatCriterion::FacultyEvenlySpread { priority, .. } => *priority,
@@ -124,6 +142,31 @@ impl SatCriterion {
             SatCriterion::TimePatternMatch { priority, .. } => *priority,
         }
     }
+
+    // Gets the sections this criterion names directly (faculty criteria name none)
+    pub fn sections(&self) -> Vec<usize> {
+        match self {
+            SatCriterion::Conflict { sections, .. } => sections.to_vec(),
+            SatCriterion::AntiConflict { single, group, .. } => {
+                let mut lst = group.clone();
+                lst.push(*single);
+                lst
+            }
+            SatCriterion::RoomPreference { section, .. }
+            | SatCriterion::RoomCapacity { section, .. }
+            | SatCriterion::SectionMoved { section, .. }
+            | SatCriterion::TimeSlotPreference { section, .. } => vec![*section],
+            SatCriterion::TimePatternMatch { sections, .. } => sections.clone(),
+            SatCriterion::FacultyDaysOff { .. }
+            | SatCriterion::FacultyEvenlySpread { .. }
+            | SatCriterion::FacultyNoRoomSwitch { .. }
+            | SatCriterion::FacultyTooManyRooms { .. }
+            | SatCriterion::FacultyGapTooLong { .. }
+            | SatCriterion::FacultyGapTooShort { .. }
+            | SatCriterion::FacultyClusterTooLong { .. }
+            | SatCriterion::FacultyClusterTooShort { .. } => Vec::new(),
+        }
+    }
 }
 
 // Container for all SAT criteria, organized by priority level
@@ -199,6 +242,28 @@ impl SatCriteria {
                     }
                 }
 
+                Criterion::RoomTooSmall { priority, section, rooms } => {
+                    criteria.add_criterion(SatCriterion::RoomCapacity {
+                        section: *section,
+                        rooms: rooms.clone(),
+                        too_small: true,
+                        priority: *priority,
+                    });
+                }
+
+                Criterion::RoomTooLarge { priority, section, rooms } => {
+                    criteria.add_criterion(SatCriterion::RoomCapacity {
+                        section: *section,
+                        rooms: rooms.clone(),
+                        too_small: false,
+                        priority: *priority,
+                    });
+                }
+
+                &Criterion::SectionMoved { priority, section, time_slot, room } => {
+                    criteria.add_criterion(SatCriterion::SectionMoved { section, time_slot, room, priority });
+                }
+
                 Criterion::FacultyPreference {
                     faculty,
                     sections: _, // We'll get these from the Faculty struct
@@ -26,6 +26,14 @@ pub fn encode_criterion(
             encode_room_preference(input, encoding, *priority, *section, *room)
         }
 
+        SatCriterion::RoomCapacity { section, rooms, too_small, priority } => {
+            encode_room_capacity(input, encoding, *priority, *section, rooms, *too_small)
+        }
+
+        SatCriterion::SectionMoved { section, time_slot, room, priority } => {
+            encode_section_moved(input, encoding, *priority, *section, *time_slot, *room)
+        }
+
         SatCriterion::TimeSlotPreference { section, time_slot, priority } => {
             encode_time_slot_preference(input, encoding, *priority, *section, *time_slot)
         }
@@ -356,6 +364,78 @@ fn encode_room_preference(
     Ok(())
 }
 
+// Encode a room capacity constraint
+//
+// The section is penalized once if it is placed in any of the listed rooms,
+// which are the rooms with too few seats (or too many) for its enrollment.
+fn encode_room_capacity(
+    input: &Input,
+    encoding: &mut Encoding,
+    priority: u8,
+    section: usize,
+    rooms: &[usize],
+    too_small: bool,
+) -> Result<()> {
+    if section >= input.sections.len() {
+        return err(format!("Section index {} in room capacity not found", section));
+    }
+    let section_name = &input.sections[section].name;
+    let hallpass = encoding.new_hallpass(
+        priority,
+        format!("{} should not be in a room that is too {}", section_name, if too_small { "small" } else { "large" }),
+    );
+
+    for &room in rooms {
+        let Some(&room_var) = encoding.section_room_vars.get(&(section, room)) else {
+            return err(format!("Missing variable for section {}, room {}", section, room));
+        };
+
+        // Encode: room_var -> hallpass
+        encoding.add_clause(vec![-room_var, hallpass]);
+    }
+
+    Ok(())
+}
+
+// Encode a penalty for a section that is not at its baseline time slot and room
+fn encode_section_moved(
+    input: &Input,
+    encoding: &mut Encoding,
+    priority: u8,
+    section: usize,
+    time_slot: usize,
+    room: Option<usize>,
+) -> Result<()> {
+    if section >= input.sections.len() {
+        return err(format!("Section index {} in section moved not found", section));
+    }
+    let section_name = &input.sections[section].name;
+    let hallpass = encoding.new_hallpass(priority, format!("{} should stay at its baseline placement", section_name));
+
+    // a baseline spot that is no longer an option means the section has to move
+    let Some(&time_var) = encoding.section_time_vars.get(&(section, time_slot)) else {
+        encoding.add_clause(vec![hallpass]);
+        return Ok(());
+    };
+
+    // Encode: !time_var -> hallpass
+    encoding.add_clause(vec![time_var, hallpass]);
+
+    match room {
+        Some(room) => match encoding.section_room_vars.get(&(section, room)) {
+            // Encode: !room_var -> hallpass
+            Some(&room_var) => encoding.add_clause(vec![room_var, hallpass]),
+            None => encoding.add_clause(vec![hallpass]),
+        },
+
+        // a section with rooms always gets one, so it cannot stay roomless
+        None if !input.sections[section].rooms.is_empty() => encoding.add_clause(vec![hallpass]),
+        None => {}
+    }
+
+    Ok(())
+}
+
 // A time pattern is defined by the number of days and the duration
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Pattern {
@@ -2343,18 +2423,24 @@ mod tests {
             rooms: rooms.iter().map(|&room| RoomWithOptionalPriority { room, priority: None }).collect(),
             time_slots: vec![TimeSlotWithOptionalPriority { time_slot, priority: None }],
             faculty: vec![0],
+            enrollment: None,
+            pinned: None,
+            baseline: None,
+            background: false,
             hard_conflicts: vec![],
             criteria: vec![],
             neighbors: vec![],
         }
     }
 
-    #[test]
-    fn no_room_switch_clause_allows_an_intervening_class() {
+    fn two_room_input(sections: Vec<Section>) -> Input {
         let monday = Days::parse("M").unwrap();
-        let input = Input {
+        Input {
             term_name: "test".to_string(),
-            rooms: vec![Room { name: "A".to_string() }, Room { name: "B".to_string() }],
+            departments: vec![],
+            background_id: None,
+            baseline: None,
+            rooms: vec![Room { name: "A".to_string(), capacity: 30 }, Room { name: "B".to_string(), capacity: 60 }],
             time_slots: (0..3)
                 .map(|index| TimeSlot {
                     name: format!("T{index}"),
@@ -2364,11 +2450,55 @@ mod tests {
                 })
                 .collect(),
             faculty: vec![Faculty { name: "Faculty".to_string(), sections: vec![0, 1, 2] }],
-            sections: vec![section("A", 0, &[0, 1]), section("B", 1, &[]), section("C", 2, &[0, 1])],
+            sections,
             criteria: vec![],
             faculty_preference_priority_policy: FacultyPreferencePriorityPolicy::Stated,
             time_slot_conflicts: vec![vec![true, false, false], vec![false, true, false], vec![false, false, true]],
-        };
+        }
+    }
+
+    #[test]
+    fn room_capacity_uses_one_hallpass_for_every_listed_room() {
+        let input = two_room_input(vec![section("A", 0, &[0, 1])]);
+        let mut encoding = Encoding::new();
+        for room in 0..2 {
+            let room_var = encoding.new_var();
+            encoding.section_room_vars.insert((0, room), room_var);
+        }
+
+        encode_room_capacity(&input, &mut encoding, 5, 0, &[0, 1], true).unwrap();
+        assert_eq!(encoding.hallpasses[&5].len(), 1);
+        let hallpass = *encoding.hallpasses[&5].iter().next().unwrap();
+        for room in 0..2 {
+            let room_var = encoding.section_room_vars[&(0, room)];
+            assert!(encoding.clauses.iter().any(|clause| clause == &vec![-room_var, hallpass]));
+        }
+    }
+
+    #[test]
+    fn section_moved_requires_hallpass_away_from_baseline() {
+        let input = two_room_input(vec![section("A", 0, &[0, 1])]);
+        let mut encoding = Encoding::new();
+        let time_var = encoding.new_var();
+        encoding.section_time_vars.insert((0, 0), time_var);
+        let room_var = encoding.new_var();
+        encoding.section_room_vars.insert((0, 1), room_var);
+
+        encode_section_moved(&input, &mut encoding, 5, 0, 0, Some(1)).unwrap();
+        let hallpass = *encoding.hallpasses[&5].iter().next().unwrap();
+        assert!(encoding.clauses.iter().any(|clause| clause == &vec![time_var, hallpass]));
+        assert!(encoding.clauses.iter().any(|clause| clause == &vec![room_var, hallpass]));
+
+        // a baseline time slot the section can no longer use always costs a hallpass
+        encode_section_moved(&input, &mut encoding, 6, 0, 2, Some(1)).unwrap();
+        let hallpass = *encoding.hallpasses[&6].iter().next().unwrap();
+        assert!(encoding.clauses.iter().any(|clause| clause == &vec![hallpass]));
+    }
+
+    #[test]
+    fn no_room_switch_clause_allows_an_intervening_class() {
+        let monday = Days::parse("M").unwrap();
+        let input = two_room_input(vec![section("A", 0, &[0, 1]), section("B", 1, &[]), section("C", 2, &[0, 1])]);
         let mut encoding = Encoding::new();
         for section in 0..3 {
             let time_var = encoding.new_var();
@@ -1,21 +1,63 @@
-use super::SatOpts;
 use super::cnf::Encoding;
 use super::error::{Result, err};
 use super::input::*;
+use super::interrupt::*;
+use super::print::*;
 use super::sat_criteria::*;
 use super::sat_encoders::*;
 use super::score::*;
 use super::solver::*;
-use std::collections::{HashMap, HashSet};
+use super::{LnsOpts, SatOpts};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::Write;
 use std::time::Instant;
 
+// How the minimum violation count at each priority level is found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SatSearch {
+    // try 0, 1, 2, ... until a bound is satisfiable
+    Linear,
+    // try 0, then double the bound until it is satisfiable, then bisect
+    Bisect,
+    // collect disjoint unsatisfiable cores for a lower bound, then bisect
+    CoreGuided,
+}
+
+impl SatSearch {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Bisect => "bisect",
+            Self::CoreGuided => "core",
+        }
+    }
+}
+
+impl std::str::FromStr for SatSearch {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Self::Linear),
+            "bisect" => Ok(Self::Bisect),
+            "core" => Ok(Self::CoreGuided),
+            _ => Err(format!("unknown search mode \"{}\" (expected linear, bisect, or core)", s)),
+        }
+    }
+}
+
 // Generate a schedule using the SAT-based approach.
 //
 // This function encodes the scheduling problem as a SAT instance, solves it using an
-// incremental approach to minimize violations at each priority level, and then constructs
+// level-by-level approach to minimize violations at each priority level, and then constructs
 // a schedule from the solution.
-pub fn generate_schedule(config: &SatOpts, input: &Input) -> Result<Schedule> {
+//
+// A single encoding is kept for the whole search. Each priority level adds its criteria
+// and a totalizer over its hallpasses, bounds are tried as assumptions on the totalizer
+// outputs, and the bound that succeeds is made permanent before moving to the next level.
+// The encoding keeps one incremental solver for the whole search, so each query only loads
+// the clauses added since the last one and keeps what earlier queries learned.
+pub fn generate_schedule(config: &SatOpts, input: &Input, provenance: &Provenance) -> Result<Schedule> {
     let start_time = Instant::now();
     println!("Starting SAT-based schedule generation");
 
@@ -27,13 +69,16 @@ pub fn generate_schedule(config: &SatOpts, input: &Input) -> Result<Schedule> {
         sat_criteria.max_priority() + 1
     );
 
+    // Encode everything that does not depend on the priority levels
+    let mut encoding = create_base_encoding(input)?;
+
     // Track maximum violations allowed using a score vector
     let mut max_violations = Score::new();
 
     // the best schedule so far
     let mut best = None;
     let mut placement_id = None;
-    println!("Searching for minimal score:");
+    println!("Searching for minimal score ({} search):", config.search.name());
 
     // Process each priority level in order
     let max_priority = sat_criteria.max_priority();
@@ -44,14 +89,28 @@ pub fn generate_schedule(config: &SatOpts, input: &Input) -> Result<Schedule> {
         }
 
         // solve at this priority level, updating max_violations in place
-        best = solve_at_priority_level(input, &sat_criteria, priority, &mut max_violations)?;
-        if best.is_none() {
-            if priority == 0 {
-                return err("Failed to find a solution that satisfies hard constraints");
+        let schedule =
+            solve_at_priority_level(input, &sat_criteria, &mut encoding, priority, config.search, &mut max_violations)?;
+
+        // an interrupt stops the solve in progress and the search with it; a
+        // model found for this level is kept if it beats the last level's
+        if interrupted() {
+            println!("\r  Interrupted at priority level {}, keeping best schedule so far", priority);
+            if let Some(mut schedule) = schedule
+                && best.as_ref().is_none_or(|b: &Schedule| schedule.score < b.score)
+            {
+                schedule.optimum_score_prefix = max_violations.levels[..priority as usize].to_vec();
+                let msg = format!("SAT generated: interrupted at priority level {}", priority);
+                save_schedule(&config.db_path, input, &schedule, &msg, provenance, placement_id)?;
+                best = Some(schedule);
             }
+            break;
+        }
+        if schedule.is_none() {
             println!("  Failed to find solution at priority level {}, keeping best schedule so far", priority);
             break;
         }
+        best = schedule;
 
         // save after each priority level
         let msg = if priority == max_priority {
@@ -64,13 +123,14 @@ pub fn generate_schedule(config: &SatOpts, input: &Input) -> Result<Schedule> {
         };
         schedule.optimum_score_prefix = schedule.score.levels[..=priority as usize].to_vec();
         print!("\r");
-        placement_id = Some(save_schedule(&config.db_path, input, schedule, msg.as_str(), placement_id)?);
+        placement_id = Some(save_schedule(&config.db_path, input, schedule, msg.as_str(), provenance, placement_id)?);
     }
     println!("\r{}    ", max_violations);
 
     if let Some(schedule) = best {
         // Sanity check: compare max_violations with schedule score
-        if max_violations != schedule.score {
+        // an interrupted search may stop with bounds that were never met
+        if max_violations != schedule.score && !interrupted() {
             println!("\nWARNING: Inconsistency detected in violation counts:");
             println!("  Search algorithm found: {}", max_violations);
             println!("  Solution reports:       {}", schedule.score);
@@ -84,67 +144,673 @@ pub fn generate_schedule(config: &SatOpts, input: &Input) -> Result<Schedule> {
     }
 }
 
+// Prove the lexicographic optimum one priority level at a time without building schedules.
+//
+// Each longer prefix is handed to `publish` as soon as it is proven. Every schedule for the
+// input scores at least the prefix followed by zeros, so the local searches use it as a
+// lower bound, and one that meets it cannot be improved. Stops early if interrupted.
+pub fn prove_optimum_prefix(input: &Input, search: SatSearch, mut publish: impl FnMut(&[ScoreLevel])) -> Result<()> {
+    let sat_criteria = SatCriteria::from_input(input)?;
+    let mut encoding = create_base_encoding(input)?;
+    let mut max_violations = Score::new();
+    for priority in 0..=sat_criteria.max_priority() {
+        let constraints = sat_criteria.criteria_at_priority(priority);
+        if !constraints.is_empty() || priority == 0 {
+            for constraint in constraints {
+                encode_criterion(input, &mut encoding, constraint, &sat_criteria)?;
+            }
+            let found = minimize_at_priority_level(&mut encoding, priority, search, &mut max_violations, None, false)?;
+
+            // an interrupted search leaves a bound that was never proven
+            if interrupted() {
+                break;
+            }
+            if found.is_none() {
+                return err(format!("no schedule is possible at priority level {}", priority));
+            }
+        }
+        publish(&max_violations.levels[..=priority as usize]);
+    }
+
+    Ok(())
+}
+
 // Solve for a specific priority level, finding minimum violations.
+//
+// The criteria at this level are added to the shared encoding. Candidate bounds are
+// passed as assumptions so the encoding itself is not modified between attempts, and
+// once the minimum is found it is added as a permanent clause for the levels that follow.
 fn solve_at_priority_level(
     input: &Input,
     sat_criteria: &SatCriteria,
+    encoding: &mut Encoding,
     priority: u8,
+    search: SatSearch,
     max_violations: &mut Score,
 ) -> Result<Option<Schedule>> {
     // Get constraints at this priority level
     let constraints = sat_criteria.criteria_at_priority(priority);
-    let criteria_count = constraints.len();
-
-    // Reset violations at this priority level to start at 0
-    max_violations.levels[priority as usize] = 0;
-
-    // Try to solve with increasing number of violations until we find a solution
-    while max_violations.levels[priority as usize] <= criteria_count as i16 {
-        // Create the SAT instance using current violations from max_violations
-        let encoding = create_sat_instance(input, sat_criteria, max_violations, priority)?;
-
-        // print progress display
-        print!("\r<");
-        let mut sep = "";
-        for p in 0..=priority {
-            if max_violations.levels[p as usize] > 0 || p == priority {
-                print!("{}{}×{}", sep, p, max_violations.levels[p as usize]);
-                sep = ",";
-            }
+
+    // Encode each constraint at this level
+    for constraint in constraints {
+        encode_criterion(input, encoding, constraint, sat_criteria)?;
+    }
+
+    let Some(model) = minimize_at_priority_level(encoding, priority, search, max_violations, None, true)? else {
+        if priority == 0 && !interrupted() {
+            return err("No solution using only hard constraints (run \"marmot diagnose\" to see why)");
         }
-        let _ = std::io::stdout().flush();
+        return Ok(None);
+    };
+
+    // Convert the SAT solution into a schedule
+    Ok(Some(decode_solution(input, encoding, &model, priority)?))
+}
 
-        // Solve the SAT instance
-        match encoding.solve() {
-            Ok(Some(model)) => {
-                // Convert the SAT solution into a schedule
-                return Ok(Some(decode_solution(input, &encoding, &model, priority)?));
+// Find the fewest violations at one priority level that are possible given the bounds
+// already locked in for earlier levels, lock that bound in as well, and return a model
+// that meets it. Returns None if nothing is possible.
+//
+// With a background, every query also assumes its literals, and the bound is locked in by
+// adding to the background instead of to the encoding, which is left as it was found.
+fn minimize_at_priority_level(
+    encoding: &mut Encoding,
+    priority: u8,
+    search: SatSearch,
+    max_violations: &mut Score,
+    background: Option<&mut Vec<i32>>,
+    show_progress: bool,
+) -> Result<Option<HashSet<i32>>> {
+    // Collect hallpass variables in a stable order
+    let mut hallpass_vars: Vec<i32> = encoding.hallpasses.get(&priority).into_iter().flatten().copied().collect();
+    hallpass_vars.sort_unstable();
+    let mut level = LevelSearch {
+        priority,
+        hallpass_vars,
+        background: background.as_ref().map_or_else(Vec::new, |background| background.to_vec()),
+        show_progress,
+    };
+
+    // hard constraints are all or nothing, so only zero violations is worth trying
+    let found = if priority == 0 {
+        level.solve(encoding, max_violations, 0)?.map(|model| (0, model))
+    } else {
+        match search {
+            SatSearch::Linear => linear_search(&mut level, encoding, max_violations)?,
+            SatSearch::Bisect => bisect_search(&mut level, encoding, max_violations)?,
+            SatSearch::CoreGuided => core_guided_search(&mut level, encoding, max_violations)?,
+        }
+    };
+    let Some((bound, model)) = found else {
+        return Ok(None);
+    };
+
+    // lock in this bound for all later priority levels
+    let locked = level.assumptions(encoding, bound);
+    match background {
+        Some(background) => background.extend(locked),
+        None => {
+            for lit in locked {
+                encoding.add_clause(vec![lit]);
             }
-            Ok(None) => {
-                // Increment violations at this priority level and try again
-                max_violations.levels[priority as usize] += 1;
+        }
+    }
+    max_violations.levels[priority as usize] = bound as i16;
 
-                // quit early if we fail to handle hard constraints
-                if priority == 0 {
-                    return err("No solution using only hard constraints");
-                }
+    Ok(Some(model))
+}
+
+// The hallpasses at one priority level and the literals assumed in every query about it.
+struct LevelSearch {
+    priority: u8,
+    hallpass_vars: Vec<i32>,
+    background: Vec<i32>,
+    show_progress: bool,
+}
+
+impl LevelSearch {
+    // The assumptions that allow at most `bound` hallpasses at this level.
+    fn assumptions(&mut self, encoding: &mut Encoding, bound: usize) -> Vec<i32> {
+        if bound >= self.hallpass_vars.len() {
+            // every criterion may be violated, so there is nothing to assume
+            Vec::new()
+        } else if bound == 0 {
+            // no violations allowed: assume every hallpass is false
+            self.hallpass_vars.iter().map(|&var| -var).collect()
+        } else {
+            // limited violations allowed: assume the totalizer output for bound+1 is false
+            vec![encoding.hallpass_limit(self.priority, bound)]
+        }
+    }
+
+    // Try to solve with at most `bound` violations at this level.
+    fn solve(
+        &mut self,
+        encoding: &mut Encoding,
+        max_violations: &mut Score,
+        bound: usize,
+    ) -> Result<Option<HashSet<i32>>> {
+        max_violations.levels[self.priority as usize] = bound as i16;
+        if self.show_progress {
+            print_progress(max_violations, self.priority);
+        }
+        let assumptions = self.assumptions(encoding, bound);
+        self.solve_with(encoding, &assumptions)
+    }
+
+    fn solve_with(&self, encoding: &mut Encoding, assumptions: &[i32]) -> Result<Option<HashSet<i32>>> {
+        solve_assuming(encoding, &[&self.background, assumptions].concat())
+    }
+
+    // A model may use fewer hallpasses than its bound allowed.
+    fn violations(&self, model: &HashSet<i32>) -> usize {
+        self.hallpass_vars.iter().filter(|var| model.contains(var)).count()
+    }
+}
+
+fn solve_assuming(encoding: &mut Encoding, assumptions: &[i32]) -> Result<Option<HashSet<i32>>> {
+    match encoding.solve_with_assumptions(assumptions) {
+        Ok(model) => Ok(model),
+        Err(e) => err(format!("Error solving SAT instance: {}", e)),
+    }
+}
+
+// Try 0, 1, 2, ... violations until one succeeds.
+fn linear_search(
+    level: &mut LevelSearch,
+    encoding: &mut Encoding,
+    max_violations: &mut Score,
+) -> Result<Option<(usize, HashSet<i32>)>> {
+    for bound in 0..=level.hallpass_vars.len() {
+        if interrupted() {
+            break;
+        }
+        if let Some(model) = level.solve(encoding, max_violations, bound)? {
+            return Ok(Some((bound, model)));
+        }
+    }
+    Ok(None)
+}
+
+// Try zero violations, then double the bound until it succeeds, then bisect.
+// Most levels need zero or a handful of violations, so this rarely takes more
+// than a few extra solves, but levels with dozens of unavoidable violations
+// take a logarithmic number of solves instead of a linear one.
+fn bisect_search(
+    level: &mut LevelSearch,
+    encoding: &mut Encoding,
+    max_violations: &mut Score,
+) -> Result<Option<(usize, HashSet<i32>)>> {
+    let limit = level.hallpass_vars.len();
+    let mut low = 0;
+    let mut bound = 0;
+    loop {
+        if let Some(model) = level.solve(encoding, max_violations, bound)? {
+            let high = level.violations(&model);
+            return Ok(Some(bisect(level, encoding, max_violations, low, high, model)?));
+        }
+        if bound >= limit || interrupted() {
+            return Ok(None);
+        }
+        low = bound + 1;
+        bound = (bound * 2).clamp(1, limit);
+    }
+}
+
+// Core-guided search for the minimum number of violations.
+//
+// Every unsatisfiable subset of "this criterion is met" assumptions forces at least
+// one violation, so a collection of disjoint cores is a lower bound. Cores are found
+// by assuming every remaining hallpass is false, taking the assumptions the solver reports
+// as failed (shrunk a little further, see minimize_core), and then dropping that core from
+// the assumptions. Once the remaining assumptions are satisfiable the model gives an upper
+// bound, and the gap (usually zero) is bisected.
+fn core_guided_search(
+    level: &mut LevelSearch,
+    encoding: &mut Encoding,
+    max_violations: &mut Score,
+) -> Result<Option<(usize, HashSet<i32>)>> {
+    let mut remaining: Vec<i32> = level.hallpass_vars.iter().map(|&var| -var).collect();
+    let mut low = 0;
+    let model = loop {
+        max_violations.levels[level.priority as usize] = low as i16;
+        if level.show_progress {
+            print_progress(max_violations, level.priority);
+        }
+        if let Some(model) = level.solve_with(encoding, &remaining)? {
+            break model;
+        }
+
+        let core = minimize_core(encoding, &level.background, &remaining)?;
+        if core.is_empty() {
+            // unsatisfiable no matter what is violated at this level
+            return Ok(None);
+        }
+        remaining.retain(|lit| !core.contains(lit));
+        low += 1;
+        if interrupted() {
+            return Ok(None);
+        }
+    };
+
+    let high = level.violations(&model);
+    Ok(Some(bisect(level, encoding, max_violations, low, high, model)?))
+}
+
+// The assumptions among `candidates` that the last query, which must have been unsatisfiable
+// with `background` and `candidates` assumed, needed to refute it. The failed set the solver
+// reports is not always minimal, so it is re-solved on its own until it stops shrinking;
+// that usually takes one or two more solves. Empty if `background` alone is unsatisfiable.
+fn minimize_core(encoding: &mut Encoding, background: &[i32], candidates: &[i32]) -> Result<Vec<i32>> {
+    let mut core = encoding.failed_assumptions(candidates);
+    while !core.is_empty() {
+        solve_assuming(encoding, &[background, &core].concat())?;
+
+        // an interrupted solve fails nothing, so keep the core from before it
+        let smaller = encoding.failed_assumptions(&core);
+        if smaller.is_empty() || smaller.len() == core.len() {
+            break;
+        }
+        core = smaller;
+    }
+
+    Ok(core)
+}
+
+// Bisect between a bound known to fail (everything below `low`) and a bound known
+// to succeed (`high`, with its model), returning the smallest bound that succeeds.
+fn bisect(
+    level: &mut LevelSearch,
+    encoding: &mut Encoding,
+    max_violations: &mut Score,
+    mut low: usize,
+    mut high: usize,
+    mut best: HashSet<i32>,
+) -> Result<(usize, HashSet<i32>)> {
+    while low < high && !interrupted() {
+        let mid = (low + high) / 2;
+        match level.solve(encoding, max_violations, mid)? {
+            Some(model) => {
+                high = level.violations(&model).min(mid);
+                best = model;
             }
-            Err(e) => {
-                return err(format!("Error solving SAT instance: {}", e));
+            None => low = mid + 1,
+        }
+    }
+    Ok((high, best))
+}
+
+fn print_progress(max_violations: &Score, priority: u8) {
+    print!("\r<");
+    let mut sep = "";
+    for p in 0..=priority {
+        if max_violations.levels[p as usize] > 0 || p == priority {
+            print!("{}{}×{}", sep, p, max_violations.levels[p as usize]);
+            sep = ",";
+        }
+    }
+    let _ = std::io::stdout().flush();
+}
+
+// Improve a schedule with large neighborhood search.
+//
+// The whole problem is encoded once, into one incremental solver. Each iteration frees a
+// neighborhood of sections, assumes every other section stays where the schedule has it,
+// and minimizes the violations level by level just as a full SAT run does. The freed
+// sections' current placements are one possible completion, so the result is never worse;
+// equal results are kept to drift across plateaus, and better ones are saved.
+pub fn large_neighborhood_search(
+    config: &LnsOpts,
+    input: &Input,
+    schedule: &mut Schedule,
+    provenance: &Provenance,
+    rng: &mut fastrand::Rng,
+) -> Result<()> {
+    let start_time = Instant::now();
+    let sat_criteria = SatCriteria::from_input(input)?;
+    let max_priority = sat_criteria.max_priority();
+    let mut encoding = create_base_encoding(input)?;
+    for priority in 0..=max_priority {
+        for constraint in sat_criteria.criteria_at_priority(priority) {
+            encode_criterion(input, &mut encoding, constraint, &sat_criteria)?;
+        }
+    }
+    println!(
+        "encoded {} constraints as {} clauses in {}",
+        commas(sat_criteria.total_criteria_count()),
+        commas(encoding.clauses.len()),
+        ms_to_string(start_time.elapsed().as_millis())
+    );
+
+    let movable: Vec<usize> =
+        (0..input.sections.len()).filter(|&section| !input.sections[section].is_pinned()).collect();
+    if movable.is_empty() {
+        return err("no sections can be moved");
+    }
+
+    let start = Instant::now();
+    let mut save_id = None;
+    let mut last_update = 0;
+    let mut iterations: u64 = 0;
+    loop {
+        if interrupted() {
+            println!("interrupted, stopping search");
+            break;
+        }
+        let elapsed = start.elapsed().as_secs();
+        if config.iterations > 0 && iterations >= config.iterations
+            || config.iterations == 0 && elapsed >= config.solve_seconds
+        {
+            break;
+        }
+        if elapsed >= last_update + config.update_seconds {
+            last_update = elapsed;
+            println!("{}: best {} after {} iterations", sec_to_string(elapsed), schedule.score, commas(iterations));
+        }
+        iterations += 1;
+
+        let (neighborhood, free) = choose_neighborhood(input, schedule, &movable, config.size, rng);
+        let completed = complete_neighborhood(input, &mut encoding, max_priority, schedule, &free, config.search)?;
+
+        // a completion cut short by an interrupt was never minimized
+        if interrupted() {
+            println!("interrupted, stopping search");
+            break;
+        }
+        let Some(mut candidate) = completed else {
+            continue;
+        };
+        if candidate.score > schedule.score {
+            println!(
+                "\nWARNING: completion of {} scored worse than the schedule it came from",
+                neighborhood.describe(input)
+            );
+            continue;
+        }
+        candidate.optimum_score_prefix = schedule.optimum_score_prefix.clone();
+        let improved = candidate.score < schedule.score;
+        *schedule = candidate;
+        if !improved {
+            continue;
+        }
+
+        println!(
+            "new best {} from freeing {} section{} ({})",
+            schedule.score,
+            free.len(),
+            if free.len() == 1 { "" } else { "s" },
+            neighborhood.describe(input)
+        );
+        let comment = format!(
+            "LNS after {} iteration{} over {}",
+            commas(iterations),
+            if iterations == 1 { "" } else { "s" },
+            ms_to_string(start.elapsed().as_millis())
+        );
+        save_id = Some(save_schedule(&config.db_path, input, schedule, &comment, provenance, save_id)?);
+    }
+    println!("solved {} neighborhoods in {}", commas(iterations), ms_to_string(start.elapsed().as_millis()));
+
+    Ok(())
+}
+
+// The kinds of neighborhood that large neighborhood search frees.
+#[derive(Clone, Copy)]
+enum Neighborhood {
+    // every section a faculty member teaches
+    Faculty(usize),
+    // every section placed in a room
+    Room(usize),
+    // every section placed at a time that overlaps a time slot
+    TimeBand(usize),
+    // a random cluster of sections that share criteria with a section
+    Neighbors(usize),
+}
+
+impl Neighborhood {
+    fn describe(&self, input: &Input) -> String {
+        match *self {
+            Neighborhood::Faculty(faculty) => format!("faculty {}", input.faculty[faculty].name),
+            Neighborhood::Room(room) => format!("room {}", input.rooms[room].name),
+            Neighborhood::TimeBand(time_slot) => format!("times overlapping {}", input.time_slots[time_slot].name),
+            Neighborhood::Neighbors(section) => format!("neighbors of {}", input.sections[section].name),
+        }
+    }
+}
+
+// Pick a neighborhood around a section that contributes to the score (or any movable
+// section if none do) and list the sections it frees, which always include any
+// sections that are not placed.
+fn choose_neighborhood(
+    input: &Input,
+    schedule: &Schedule,
+    movable: &[usize],
+    size: usize,
+    rng: &mut fastrand::Rng,
+) -> (Neighborhood, Vec<usize>) {
+    let penalized: Vec<usize> =
+        movable.iter().copied().filter(|&section| !schedule.placements[section].score.is_zero()).collect();
+    let pool = if penalized.is_empty() { movable } else { &penalized };
+    let anchor = pool[rng.usize(..pool.len())];
+
+    let mut kinds = vec![Neighborhood::Neighbors(anchor)];
+    kinds.extend(input.sections[anchor].faculty.iter().map(|&faculty| Neighborhood::Faculty(faculty)));
+    if let Some(room) = schedule.placements[anchor].room {
+        kinds.push(Neighborhood::Room(room));
+    }
+    if let Some(time_slot) = schedule.placements[anchor].time_slot {
+        kinds.push(Neighborhood::TimeBand(time_slot));
+    }
+    let neighborhood = kinds[rng.usize(..kinds.len())];
+
+    let mut free: Vec<usize> = match neighborhood {
+        Neighborhood::Faculty(faculty) => {
+            movable.iter().copied().filter(|&section| input.sections[section].faculty.contains(&faculty)).collect()
+        }
+        Neighborhood::Room(room) => {
+            movable.iter().copied().filter(|&section| schedule.placements[section].room == Some(room)).collect()
+        }
+        Neighborhood::TimeBand(time_slot) => movable
+            .iter()
+            .copied()
+            .filter(|&section| {
+                schedule.placements[section].time_slot.is_some_and(|other| input.time_slot_conflicts[time_slot][other])
+            })
+            .collect(),
+        Neighborhood::Neighbors(section) => {
+            // grow the cluster outward, taking neighbors in random order
+            let mut cluster = vec![section];
+            let mut i = 0;
+            while i < cluster.len() && cluster.len() < size {
+                let mut next: Vec<usize> = input.sections[cluster[i]]
+                    .neighbors
+                    .iter()
+                    .copied()
+                    .filter(|&other| !cluster.contains(&other) && !input.sections[other].is_pinned())
+                    .collect();
+                rng.shuffle(&mut next);
+                next.truncate(size - cluster.len());
+                cluster.extend(next);
+                i += 1;
             }
+            cluster
+        }
+    };
+    for &section in movable {
+        if !schedule.is_placed(section) && !free.contains(&section) {
+            free.push(section);
         }
     }
 
-    Ok(None)
+    (neighborhood, free)
 }
 
-// Create a SAT instance for the timetabling problem.
-fn create_sat_instance(
+// The lexicographically best placement of the freed sections with every other section
+// fixed where the schedule has it, or None if the freed sections cannot all be placed.
+// Everything specific to this neighborhood is assumed rather than added to the encoding.
+fn complete_neighborhood(
     input: &Input,
-    sat_criteria: &SatCriteria,
-    max_violations: &Score,
-    current_priority: u8,
-) -> Result<Encoding> {
+    encoding: &mut Encoding,
+    max_priority: u8,
+    schedule: &Schedule,
+    free: &[usize],
+    search: SatSearch,
+) -> Result<Option<Schedule>> {
+    let mut fixed = Vec::new();
+    for (section, placement) in schedule.placements.iter().enumerate() {
+        if free.contains(&section) {
+            continue;
+        }
+        let Some(&time_var) =
+            placement.time_slot.and_then(|time_slot| encoding.section_time_vars.get(&(section, time_slot)))
+        else {
+            continue;
+        };
+        fixed.push(time_var);
+        if let Some(&room_var) = placement.room.and_then(|room| encoding.section_room_vars.get(&(section, room))) {
+            fixed.push(room_var);
+        }
+    }
+
+    let mut max_violations = Score::new();
+    let mut model = None;
+    for priority in 0..=max_priority {
+        // levels with nothing to violate cannot change the outcome
+        if priority > 0 && encoding.hallpasses.get(&priority).is_none_or(|hallpasses| hallpasses.is_empty()) {
+            continue;
+        }
+        let Some(found) =
+            minimize_at_priority_level(encoding, priority, search, &mut max_violations, Some(&mut fixed), false)?
+        else {
+            return Ok(None);
+        };
+        model = Some(found);
+    }
+    let Some(model) = model else {
+        return Ok(None);
+    };
+
+    Ok(Some(decode_solution(input, encoding, &model, max_priority)?))
+}
+
+// Explain why the hard constraints cannot all be met.
+//
+// Every group of hard clauses in the base encoding is switched on by its own selector,
+// and each hard criterion already has a hallpass, so each input fact is one assumption.
+// An unsatisfiable set of those assumptions, shrunk as far as the solver can cheaply
+// manage, is then reported as the facts behind it.
+pub fn diagnose_hard_constraints(input: &Input) -> Result<()> {
+    let start_time = Instant::now();
+    let sat_criteria = SatCriteria::from_input(input)?;
+
+    let mut encoding = Encoding::new();
+    encoding.track_hard_groups();
+    create_basic_variables(input, &mut encoding)?;
+    encode_basic_constraints(input, &mut encoding)?;
+    encode_room_conflicts(input, &mut encoding)?;
+    encode_pins(input, &mut encoding)?;
+
+    // each fact is the literal that switches it on, what it means, the sections involved,
+    // and the faculty they share if it is a conflict between sections with the same faculty
+    let mut facts = Vec::new();
+    for (selector, sections) in encoding.hard_groups.take().unwrap_or_default() {
+        facts.push((selector, encoding.problems[&selector].1.clone(), sections, Vec::new()));
+    }
+    for criterion in sat_criteria.criteria_at_priority(0) {
+        let last_var = encoding.last_var;
+        encode_criterion(input, &mut encoding, criterion, &sat_criteria)?;
+        let mut hallpasses: Vec<i32> =
+            encoding.hallpasses.get(&0).into_iter().flatten().copied().filter(|&var| var > last_var).collect();
+        hallpasses.sort_unstable();
+        let faculty: Vec<usize> = match *criterion {
+            SatCriterion::Conflict { sections: [a, b], .. } => input.sections[a]
+                .faculty
+                .iter()
+                .copied()
+                .filter(|faculty| input.sections[b].faculty.contains(faculty))
+                .collect(),
+            _ => Vec::new(),
+        };
+        for hallpass in hallpasses {
+            let message = describe_hard_criterion(input, criterion, &encoding.problems[&hallpass].1);
+            facts.push((-hallpass, message, criterion.sections(), faculty.clone()));
+        }
+    }
+
+    let assumptions: Vec<i32> = facts.iter().map(|(lit, _, _, _)| *lit).collect();
+    println!("Checking {} hard constraints", assumptions.len());
+    if solve_assuming(&mut encoding, &assumptions)?.is_some() {
+        println!("The hard constraints can all be satisfied");
+        return Ok(());
+    }
+    let core = minimize_core(&mut encoding, &[], &assumptions)?;
+    if core.is_empty() {
+        return err("The hard constraints are unsatisfiable even without any of the input facts");
+    }
+
+    println!("These {} hard constraints cannot all be satisfied:", core.len());
+    let mut by_faculty: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (lit, message, involved, faculty) in &facts {
+        if core.contains(lit) {
+            println!("    {}", message);
+            for &faculty in faculty {
+                by_faculty.entry(faculty).or_default().extend_from_slice(involved);
+            }
+        }
+    }
+
+    // shared faculty is the most common reason sections get in each other's way, but
+    // only worth pointing out when they have fewer time slots than sections to fill them
+    for (faculty, mut shared) in by_faculty {
+        shared.sort_unstable();
+        shared.dedup();
+        let mut time_slots: Vec<usize> = shared
+            .iter()
+            .flat_map(|&section| input.sections[section].time_slots.iter().map(|option| option.time_slot))
+            .collect();
+        time_slots.sort_unstable();
+        time_slots.dedup();
+        if time_slots.len() >= shared.len() {
+            continue;
+        }
+        let names: Vec<&str> = shared.iter().map(|&section| input.sections[section].name.as_str()).collect();
+        println!(
+            "{} share faculty {}, who has only {} available time slot{} for them",
+            join_names(&names),
+            input.faculty[faculty].name,
+            time_slots.len(),
+            if time_slots.len() == 1 { "" } else { "s" }
+        );
+    }
+    println!("Diagnosis took {:?}", start_time.elapsed());
+
+    Ok(())
+}
+
+// Hard conflicts between sections that share faculty say who they share.
+fn describe_hard_criterion(input: &Input, criterion: &SatCriterion, problem: &str) -> String {
+    let SatCriterion::Conflict { sections: [a, b], .. } = *criterion else {
+        return problem.to_string();
+    };
+    let (a, b) = (&input.sections[a], &input.sections[b]);
+    match a.faculty.iter().find(|faculty| b.faculty.contains(faculty)) {
+        Some(&faculty) => format!("{} and {} are both taught by {}", a.name, b.name, input.faculty[faculty].name),
+        None => format!("{} and {} must not overlap", a.name, b.name),
+    }
+}
+
+// e.g., "A, B and C"
+fn join_names(names: &[&str]) -> String {
+    match names {
+        [] => String::new(),
+        [only] => only.to_string(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+// Create the part of the SAT instance that is shared by every priority level:
+// the placement variables, the basic placement constraints, and room conflicts.
+fn create_base_encoding(input: &Input) -> Result<Encoding> {
     // Create CNF formula
     let mut encoding = Encoding::new();
 
@@ -157,17 +823,37 @@ fn create_sat_instance(
     // Encode room conflicts
     encode_room_conflicts(input, &mut encoding)?;
 
-    // Encode all constraints up to and including the current priority level
-    for p in 0..=current_priority {
-        // Determine max violations allowed for this priority level
-        let violations = max_violations.levels[p as usize];
+    // Fix pinned sections in place
+    encode_pins(input, &mut encoding)?;
 
-        // Encode constraints at this priority level
-        encode_constraints(input, sat_criteria, &mut encoding, p, violations)?;
+    Ok(encoding)
+}
+
+// Pinned sections must take their pinned time slot and room.
+fn encode_pins(input: &Input, encoding: &mut Encoding) -> Result<()> {
+    for (section, elt) in input.sections.iter().enumerate() {
+        let Some((time_slot, room)) = elt.pinned else {
+            continue;
+        };
+        encoding.begin_hard_group(&[section], || {
+            let what = if elt.background { "frozen in the background" } else { "pinned" };
+            format!("{} is {} at {}", elt.name, what, input.describe_placement(time_slot, room))
+        });
+        let Some(&time_var) = encoding.section_time_vars.get(&(section, time_slot)) else {
+            return err(format!("Missing variable for pinned section {}, time slot {}", section, time_slot));
+        };
+        encoding.add_clause(vec![time_var]);
+
+        if let Some(room) = room {
+            let Some(&room_var) = encoding.section_room_vars.get(&(section, room)) else {
+                return err(format!("Missing variable for pinned section {}, room {}", section, room));
+            };
+            encoding.add_clause(vec![room_var]);
+        }
+        encoding.end_hard_group();
     }
 
-    // Return the CNF formula and variable mappings
-    Ok(encoding)
+    Ok(())
 }
 
 // Create the basic variables for sections, time slots, and rooms.
@@ -211,16 +897,22 @@ fn encode_basic_constraints(input: &Input, encoding: &mut Encoding) -> Result<()
     }
 
     // Constraint 1: Each section must be assigned exactly one room (if it has available rooms)
-    for (_section, room_vars) in section_to_rooms {
+    for (section, room_vars) in section_to_rooms {
         if room_vars.is_empty() {
             continue;
         }
+        encoding.begin_hard_group(&[section], || {
+            let names: Vec<&str> =
+                input.sections[section].rooms.iter().map(|option| input.rooms[option.room].name.as_str()).collect();
+            format!("{} can only be in {}", input.sections[section].name, names.join(", "))
+        });
 
         // At least one room must be assigned
         encoding.add_clause(room_vars.clone());
 
         // At most one room must be assigned
         encoding.pairwise_at_most_one(&room_vars);
+        encoding.end_hard_group();
     }
 
     // Constraint 2: Each section must be assigned exactly one time slot
@@ -228,12 +920,21 @@ fn encode_basic_constraints(input: &Input, encoding: &mut Encoding) -> Result<()
         if time_vars.is_empty() {
             return err(format!("Section {} has no available time slots", input.sections[section].name));
         }
+        encoding.begin_hard_group(&[section], || {
+            let names: Vec<&str> = input.sections[section]
+                .time_slots
+                .iter()
+                .map(|option| input.time_slots[option.time_slot].name.as_str())
+                .collect();
+            format!("{} can only meet at {}", input.sections[section].name, names.join(", "))
+        });
 
         // At least one time slot must be assigned
         encoding.add_clause(time_vars.clone());
 
         // At most one time slot must be assigned
         encoding.pairwise_at_most_one(&time_vars);
+        encoding.end_hard_group();
     }
 
     Ok(())
@@ -263,7 +964,14 @@ fn encode_room_conflicts(input: &Input, encoding: &mut Encoding) -> Result<()> {
                 if input.sections[sections[i]].hard_conflicts.contains(&sections[j]) {
                     continue;
                 }
+                encoding.begin_hard_group(&[sections[i], sections[j]], || {
+                    format!(
+                        "{} and {} cannot both be in {} at overlapping times",
+                        input.sections[sections[i]].name, input.sections[sections[j]].name, input.rooms[room].name
+                    )
+                });
                 encode_room_conflict(input, encoding, sections[i], sections[j], room)?;
+                encoding.end_hard_group();
             }
         }
     }
@@ -323,43 +1031,6 @@ fn encode_room_conflict(
     Ok(())
 }
 
-// Encode constraints at a specific priority level.
-fn encode_constraints(
-    input: &Input,
-    sat_criteria: &SatCriteria,
-    encoding: &mut Encoding,
-    priority: u8,
-    max_violations: i16,
-) -> Result<()> {
-    // Get all constraints at this priority level
-    let constraints = sat_criteria.criteria_at_priority(priority);
-
-    // Encode each constraint
-    for constraint in constraints {
-        encode_criterion(input, encoding, constraint, sat_criteria)?;
-    }
-
-    // Collect hallpass variables to avoid borrow conflicts
-    let hallpass_vars: Vec<i32> = encoding.hallpasses.get(&priority).into_iter().flatten().copied().collect();
-
-    // Apply cardinality constraint if needed
-    if max_violations == 0 {
-        // No violations allowed: force all hallpass variables to be false
-        for &var in &hallpass_vars {
-            encoding.add_clause(vec![-var]);
-        }
-    } else if !hallpass_vars.is_empty() && max_violations < hallpass_vars.len() as i16 {
-        // Limited violations allowed: add cardinality constraint
-        if max_violations == 1 && hallpass_vars.len() <= 30 {
-            encoding.pairwise_at_most_one(&hallpass_vars);
-        } else {
-            encoding.totalizer_at_most_k(&hallpass_vars, max_violations as usize, None);
-        }
-    }
-
-    Ok(())
-}
-
 // Decode a SAT solution into a schedule.
 fn decode_solution(input: &Input, encoding: &Encoding, model: &HashSet<i32>, priority: u8) -> Result<Schedule> {
     // Create reverse lookup tables to find section/room and section/time from var
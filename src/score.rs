@@ -1,3 +1,4 @@
+use super::error::{Result, err};
 use super::input::*;
 use super::solver::*;
 use std::fmt;
@@ -46,6 +47,22 @@ pub enum Criterion {
         section: usize,
         time_slots_with_priorities: Vec<TimeSlotWithPriority>,
     },
+    RoomTooSmall {
+        priority: u8,
+        section: usize,
+        rooms: Vec<usize>,
+    },
+    RoomTooLarge {
+        priority: u8,
+        section: usize,
+        rooms: Vec<usize>,
+    },
+    SectionMoved {
+        priority: u8,
+        section: usize,
+        time_slot: usize,
+        room: Option<usize>,
+    },
     FacultyPreference {
         faculty: usize,
         sections: Vec<usize>,
@@ -120,6 +137,22 @@ pub enum Penalty {
         section: usize,
         time_slot: usize,
     },
+    RoomTooSmall {
+        priority: u8,
+        section: usize,
+        room: usize,
+    },
+    RoomTooLarge {
+        priority: u8,
+        section: usize,
+        room: usize,
+    },
+    SectionMoved {
+        priority: u8,
+        section: usize,
+        time_slot: usize,
+        room: Option<usize>,
+    },
     ClusterTooShort {
         priority: u8,
         faculty: usize,
@@ -230,6 +263,65 @@ impl Score {
     }
 }
 
+// a goal for the best score: the counts at levels 0 through last must be
+// lexicographically no worse than these, and later levels do not matter
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ScoreTarget {
+    pub levels: [ScoreLevel; PRIORITY_LEVELS],
+    pub last: u8,
+}
+
+impl ScoreTarget {
+    // written like a printed score, e.g., "<15×3,17×2>" or "15x3,17x2", with
+    // unlisted levels zero; "9x0" asks for zero at every level through 9
+    pub fn parse(s: &str) -> Result<Self> {
+        let trimmed = s.trim().trim_start_matches('<').trim_end_matches('>');
+        if trimmed == "zero" {
+            return Ok(ScoreTarget { levels: [0; PRIORITY_LEVELS], last: MAX_PRIORITY });
+        }
+        let mut target = ScoreTarget { levels: [0; PRIORITY_LEVELS], last: 0 };
+        for term in trimmed.split(',') {
+            let Some((level, count)) = term.trim().split_once(['×', 'x']) else {
+                return err(format!("unable to parse score target \"{}\": expected, e.g., <15x3,17x2>", s));
+            };
+            match (level.trim().parse::<u8>(), count.trim().parse::<ScoreLevel>()) {
+                (Ok(level), Ok(count)) if level <= MAX_PRIORITY && count >= 0 => {
+                    target.levels[level as usize] = count;
+                    target.last = target.last.max(level);
+                }
+                _ => return err(format!("unable to parse score target \"{}\": expected, e.g., <15x3,17x2>", s)),
+            }
+        }
+        Ok(target)
+    }
+
+    // the score a SAT-proven prefix allows if every later level is zero
+    pub fn from_optimum_prefix(prefix: &[ScoreLevel]) -> Self {
+        let mut target = ScoreTarget { levels: [0; PRIORITY_LEVELS], last: MAX_PRIORITY };
+        target.levels[..prefix.len()].copy_from_slice(prefix);
+        target
+    }
+
+    pub fn is_met(&self, score: &Score) -> bool {
+        let last = self.last as usize;
+        score.levels[..=last] <= self.levels[..=last]
+    }
+}
+
+impl fmt::Display for ScoreTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        write!(f, "<")?;
+        for (level, &count) in self.levels.iter().enumerate().take(self.last as usize + 1) {
+            if count != 0 || level == self.last as usize {
+                write!(f, "{sep}{level}×{count}")?;
+                sep = ",";
+            }
+        }
+        write!(f, ">")
+    }
+}
+
 impl Default for Score {
     fn default() -> Self {
         Self::new()
@@ -331,6 +423,12 @@ impl Criterion {
 
             Criterion::TimeSlotPreference { section, .. } => vec![*section],
 
+            Criterion::RoomTooSmall { section, .. } => vec![*section],
+
+            Criterion::RoomTooLarge { section, .. } => vec![*section],
+
+            Criterion::SectionMoved { section, .. } => vec![*section],
+
             Criterion::FacultyPreference { sections, .. } => sections.clone(),
 
             Criterion::SectionsWithDifferentTimePatterns { sections, .. } => sections.clone(),
@@ -418,6 +516,27 @@ impl Criterion {
                 Vec::new()
             }
 
+            &Criterion::RoomTooSmall { priority, section, ref rooms } => match schedule.placements[section].room {
+                Some(room) if rooms.contains(&room) => vec![Penalty::RoomTooSmall { priority, section, room }],
+                _ => Vec::new(),
+            },
+
+            &Criterion::RoomTooLarge { priority, section, ref rooms } => match schedule.placements[section].room {
+                Some(room) if rooms.contains(&room) => vec![Penalty::RoomTooLarge { priority, section, room }],
+                _ => Vec::new(),
+            },
+
+            &Criterion::SectionMoved { priority, section, time_slot, room } => {
+                // an unplaced section is already penalized for that
+                let placement = &schedule.placements[section];
+                match placement.time_slot {
+                    Some(my_time_slot) if my_time_slot != time_slot || placement.room != room => {
+                        vec![Penalty::SectionMoved { priority, section, time_slot, room }]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+
             Criterion::FacultyPreference {
                 faculty,
                 sections,
@@ -705,6 +824,47 @@ impl Criterion {
                 }
             }
 
+            Criterion::RoomTooSmall { priority, section, rooms } => {
+                let section = &input.sections[*section];
+                write!(
+                    &mut s,
+                    "{}: rooms too small for {} ({} students):",
+                    priority,
+                    section.name,
+                    section.enrollment.unwrap_or(0)
+                )
+                .unwrap();
+                for &room in rooms {
+                    write!(&mut s, " {}:{}", input.rooms[room].name, input.rooms[room].capacity).unwrap();
+                }
+            }
+
+            Criterion::RoomTooLarge { priority, section, rooms } => {
+                let section = &input.sections[*section];
+                write!(
+                    &mut s,
+                    "{}: rooms too large for {} ({} students):",
+                    priority,
+                    section.name,
+                    section.enrollment.unwrap_or(0)
+                )
+                .unwrap();
+                for &room in rooms {
+                    write!(&mut s, " {}:{}", input.rooms[room].name, input.rooms[room].capacity).unwrap();
+                }
+            }
+
+            &Criterion::SectionMoved { priority, section, time_slot, room } => {
+                write!(
+                    &mut s,
+                    "{}: keep {} at {}",
+                    priority,
+                    input.sections[section].name,
+                    input.describe_placement(time_slot, room)
+                )
+                .unwrap();
+            }
+
             Criterion::FacultyPreference {
                 faculty,
                 sections,
@@ -987,7 +1147,11 @@ impl Penalty {
             | Penalty::DaysEvenlySpread { faculty, .. }
             | Penalty::RoomSwitch { faculty, .. }
             | Penalty::RoomCount { faculty, .. } => Some(faculty),
-            Penalty::SoftConflict { .. } | Penalty::AntiConflict { .. } => None,
+            Penalty::SoftConflict { .. }
+            | Penalty::AntiConflict { .. }
+            | Penalty::RoomTooSmall { .. }
+            | Penalty::RoomTooLarge { .. }
+            | Penalty::SectionMoved { .. } => None,
         }
     }
 
@@ -1001,6 +1165,12 @@ impl Penalty {
 
             Penalty::TimeSlotPreference { priority, .. } => priority,
 
+            Penalty::RoomTooSmall { priority, .. } => priority,
+
+            Penalty::RoomTooLarge { priority, .. } => priority,
+
+            Penalty::SectionMoved { priority, .. } => priority,
+
             Penalty::ClusterTooShort { priority, .. } => priority,
 
             Penalty::ClusterTooLong { priority, .. } => priority,
@@ -1035,6 +1205,12 @@ impl Penalty {
 
             &Penalty::TimeSlotPreference { section, .. } => vec![section],
 
+            &Penalty::RoomTooSmall { section, .. } => vec![section],
+
+            &Penalty::RoomTooLarge { section, .. } => vec![section],
+
+            &Penalty::SectionMoved { section, .. } => vec![section],
+
             &Penalty::ClusterTooShort { faculty, .. } => input.faculty[faculty].sections.clone(),
 
             &Penalty::ClusterTooLong { faculty, .. } => input.faculty[faculty].sections.clone(),
@@ -1235,6 +1411,37 @@ impl Penalty {
                 ),
             ),
 
+            &Penalty::RoomTooSmall { priority, section, room } => (
+                priority,
+                format!(
+                    "{} has {} students but is assigned to {} with {} seats",
+                    input.sections[section].name,
+                    input.sections[section].enrollment.unwrap_or(0),
+                    input.rooms[room].name,
+                    input.rooms[room].capacity
+                ),
+            ),
+
+            &Penalty::RoomTooLarge { priority, section, room } => (
+                priority,
+                format!(
+                    "{} has only {} students but is assigned to {} with {} seats",
+                    input.sections[section].name,
+                    input.sections[section].enrollment.unwrap_or(0),
+                    input.rooms[room].name,
+                    input.rooms[room].capacity
+                ),
+            ),
+
+            &Penalty::SectionMoved { priority, section, time_slot, room } => (
+                priority,
+                format!(
+                    "{} has moved from {} in the baseline schedule",
+                    input.sections[section].name,
+                    input.describe_placement(time_slot, room)
+                ),
+            ),
+
             Penalty::SectionsWithDifferentTimePatterns { priority, faculty: owner, sections, time_slots } => {
                 (*priority, {
                     let mut faculty = owner.map_or_else(Vec::new, |faculty| vec![faculty]);
@@ -1283,3 +1490,26 @@ impl Penalty {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_targets_compare_only_through_the_last_listed_level() {
+        let target = ScoreTarget::parse("<14x2,16×5>").unwrap();
+        assert_eq!(target.last, 16);
+        assert_eq!(target.to_string(), "<14×2,16×5>");
+
+        let mut score = Score::new() + 14 + 16 + 16;
+        score += 25;
+        assert!(target.is_met(&score));
+        score += 15;
+        assert!(target.is_met(&score));
+        assert!(!target.is_met(&(Score::new() + 3)));
+
+        assert!(ScoreTarget::parse("zero").unwrap().is_met(&Score::new()));
+        assert!(ScoreTarget::parse("15").is_err());
+        assert!(ScoreTarget::parse("99x1").is_err());
+    }
+}
@@ -1,11 +1,15 @@
 #![allow(clippy::collapsible_if)]
 
 use super::input::*;
+use super::interrupt::*;
 use super::score::*;
 use super::*;
 use std::cmp::{max, min};
 use std::io::Write;
 use std::mem::take;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::Instant;
 
 //
@@ -83,7 +87,17 @@ impl Schedule {
             penalties.push(Vec::new());
         }
 
-        Schedule { placements, room_placements, penalties, score, optimum_score_prefix: Vec::new() }
+        let mut schedule = Schedule { placements, room_placements, penalties, score, optimum_score_prefix: Vec::new() };
+
+        // pinned sections are placed up front and never move after this
+        for (section, elt) in input.sections.iter().enumerate() {
+            let Some((time_slot, room)) = elt.pinned else {
+                continue;
+            };
+            let _undo = move_section(input, &mut schedule, section, time_slot, &room);
+        }
+
+        schedule
     }
 
     pub fn is_placed(&self, section: usize) -> bool {
@@ -164,10 +178,26 @@ impl Schedule {
         }
     }
 
+    // make the placement changes for a move without any scoring updates
+    pub fn make_move(&mut self, input: &Input, candidate: &Move, undo: &mut Vec<PlacementLogEntry>) {
+        let targets = candidate.targets(self);
+
+        // lift every section out first so they can trade places,
+        // then put each one down, displacing anything else in the way
+        for &(section, _, _) in &targets {
+            self.remove_placement(section, undo);
+        }
+        for (section, time_slot, room) in targets {
+            self.displace_conflicts(input, section, time_slot, &room, undo);
+            self.add_placement(section, time_slot, &room, undo);
+        }
+    }
+
     // returns values
     //   Some((time_based, taboo)):
     //   - time_based is true if the conflict would hold regardless of room
     //   - taboo indicates one or more of the conflicts were sections in the taboo list
+    //     (pinned sections are always taboo)
     //   None:
     //   - no hard conflicts
     pub fn has_hard_conflict(
@@ -188,7 +218,7 @@ impl Schedule {
                 if input.time_slot_conflicts[time_slot][other_time_slot] {
                     found = true;
                     time_based = true;
-                    if taboo.contains(&hard_conflict) {
+                    if taboo.contains(&hard_conflict) || input.sections[hard_conflict].is_pinned() {
                         with_taboo = true;
                     }
                 }
@@ -202,7 +232,7 @@ impl Schedule {
             {
                 if input.time_slot_conflicts[time_slot][other_time_slot] {
                     found = true;
-                    if taboo.contains(&hard_conflict) {
+                    if taboo.contains(&hard_conflict) || input.sections[hard_conflict].is_pinned() {
                         with_taboo = true;
                     }
                 }
@@ -237,14 +267,18 @@ pub fn move_section(
     time_slot: usize,
     maybe_room: &Option<usize>,
 ) -> PlacementLog {
+    apply_move(input, schedule, &Move::Single { section, time_slot, room: *maybe_room })
+}
+
+// make any kind of move and update the score, returning a log with
+// enough information to revert it
+pub fn apply_move(input: &Input, schedule: &mut Schedule, candidate: &Move) -> PlacementLog {
     // note: we leave unplaced section penalties in place and use them
     // to track which sections were placed before we started moving
 
     // perform the moves without any scoring updates
     let mut moves = Vec::new();
-    schedule.remove_placement(section, &mut moves);
-    schedule.displace_conflicts(input, section, time_slot, maybe_room, &mut moves);
-    schedule.add_placement(section, time_slot, maybe_room, &mut moves);
+    schedule.make_move(input, candidate, &mut moves);
 
     // gather list of sections moved
     let sections_moved = get_sections_from_log_entry_list(&moves);
@@ -303,11 +337,14 @@ fn speculative_move_section(
     time_slot: usize,
     maybe_room: &Option<usize>,
 ) -> Score {
+    speculative_move(input, schedule, &Move::Single { section, time_slot, room: *maybe_room })
+}
+
+// calculate the score delta that would happen if any kind of move was applied
+fn speculative_move(input: &Input, schedule: &mut Schedule, candidate: &Move) -> Score {
     // move the sections, which does not update scoring
     let mut moves = Vec::new();
-    schedule.remove_placement(section, &mut moves);
-    schedule.displace_conflicts(input, section, time_slot, maybe_room, &mut moves);
-    schedule.add_placement(section, time_slot, maybe_room, &mut moves);
+    schedule.make_move(input, candidate, &mut moves);
 
     // gather list of sections moved
     let sections_moved = get_sections_from_log_entry_list(&moves);
@@ -452,13 +489,290 @@ fn compute_penalties_for_criteria(input: &Input, schedule: &mut Schedule, criter
     }
 }
 
+// how gen searches onward from its starting schedule
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchStrategy {
+    // a biased random walk of big steps, each one followed by a greedy climb
+    Walk,
+    // simulated annealing over single-section moves, with a temperature at each priority level
+    Anneal,
+    // late-acceptance hill climbing over single-section moves
+    LateAcceptance,
+}
+
+impl SearchStrategy {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Walk => "walk",
+            Self::Anneal => "anneal",
+            Self::LateAcceptance => "late",
+        }
+    }
+}
+
+impl std::str::FromStr for SearchStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "walk" => Ok(Self::Walk),
+            "anneal" => Ok(Self::Anneal),
+            "late" => Ok(Self::LateAcceptance),
+            _ => Err(format!("unknown search strategy \"{}\" (expected walk, anneal, or late)", s)),
+        }
+    }
+}
+
+// the best schedule found by any walk, shared so that only improvements on it are saved
+struct SharedBest {
+    schedule: Schedule,
+    save_id: Option<i64>,
+    comment: String,
+    quit: bool,
+}
+
+// with an iteration budget the walk measures time in big steps instead of
+// seconds, so updates, bias swings, and rehoming happen at the same points in
+// every run with the same seed
+const BIG_STEPS_PER_TICK: u64 = 100;
+
+// single-section moves are much cheaper than big steps, so the annealing and
+// late-acceptance searches make many more of them per tick
+const MOVES_PER_TICK: u64 = 100_000;
+
+// with more than one thread, walk i starts from starts[i] if there is one (its own
+// warmup schedule) and from the given schedule otherwise
+#[allow(clippy::too_many_arguments)]
 pub fn solve(
     config: &GenOpts,
     input: &Input,
     schedule: &mut Schedule,
+    starts: Vec<Option<Schedule>>,
     seconds: u64,
+    provenance: &Provenance,
     save_id: &mut Option<i64>,
+    rng: &mut fastrand::Rng,
 ) -> Schedule {
+    let shared =
+        Mutex::new(SharedBest { schedule: schedule.clone(), save_id: *save_id, comment: String::new(), quit: false });
+
+    // the optimum prefix proven so far, starting with any the schedule carries; every
+    // schedule scores at least the prefix followed by zeros
+    let bound = Arc::new(Mutex::new(schedule.optimum_score_prefix.clone()));
+    let cancel = Arc::new(AtomicBool::new(false));
+    let prover = config.prove_bound.then(|| {
+        let input = input.clone();
+        let bound = Arc::clone(&bound);
+        let cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            cancel_with(cancel);
+            let result = prove_optimum_prefix(&input, SatSearch::Linear, |prefix| {
+                let mut bound = bound.lock().unwrap();
+                if prefix.len() > bound.len() {
+                    *bound = prefix.to_vec();
+                }
+            });
+            if let Err(e) = result {
+                println!("unable to prove a lower bound: {}", e);
+            }
+        })
+    });
+    let threads = search_threads(config);
+    if threads <= 1 {
+        search(config, input, schedule, seconds, &bound, provenance, &shared, "", rng);
+    } else {
+        // every walk gets its own random stream
+        let seeds: Vec<u64> = (0..threads).map(|_| rng.u64(..)).collect();
+        let mut starts = starts.into_iter();
+        thread::scope(|scope| {
+            for (i, seed) in seeds.into_iter().enumerate() {
+                let shared = &shared;
+                let bound = &bound;
+                let mut schedule = starts.next().flatten().unwrap_or_else(|| schedule.clone());
+                scope.spawn(move || {
+                    let mut rng = fastrand::Rng::with_seed(seed);
+                    let tag = format!("[{}] ", i + 1);
+                    search(config, input, &mut schedule, seconds, bound, provenance, shared, &tag, &mut rng);
+                });
+            }
+        });
+    }
+
+    // the searches are done, so stop proving and wait for the solve in progress to give up
+    if let Some(prover) = prover {
+        cancel.store(true, Ordering::SeqCst);
+        let _ = prover.join();
+    }
+
+    let mut shared = shared.into_inner().unwrap();
+
+    // every best was saved when it was found, but record that the run was cut
+    // short along with how long it actually ran
+    if interrupted()
+        && let Some(id) = shared.save_id
+        && !shared.comment.is_empty()
+    {
+        let msg = format!("{}, then interrupted", shared.comment);
+        match save_schedule(&config.db_path, input, &shared.schedule, &msg, provenance, Some(id)) {
+            Ok(new_id) => shared.save_id = Some(new_id),
+            Err(e) => println!("failed to save the best schedule after the interrupt: {}", e),
+        }
+    }
+    *save_id = shared.save_id;
+    shared.schedule
+}
+
+// the number of searches to run at once, with 0 meaning one per core
+fn search_threads(config: &GenOpts) -> usize {
+    match config.threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+}
+
+// run the warmup across the search threads, splitting --warmup-tries between
+// them, and return the schedule each thread found (if any) in thread order so
+// the walk on each thread can start from its own
+pub fn parallel_warmup(config: &GenOpts, input: &Input, rng: &mut fastrand::Rng) -> Vec<Option<Schedule>> {
+    let threads = search_threads(config);
+    if threads <= 1 {
+        return vec![warmup(input, config.warmup_seconds, config.warmup_tries, config.target, rng)];
+    }
+
+    let seeds: Vec<u64> = (0..threads).map(|_| rng.u64(..)).collect();
+    thread::scope(|scope| {
+        let handles: Vec<_> = seeds
+            .into_iter()
+            .enumerate()
+            .map(|(i, seed)| {
+                let n = threads as u64;
+                let tries = config.warmup_tries / n + u64::from((i as u64) < config.warmup_tries % n);
+                scope.spawn(move || {
+                    // with fewer tries than threads, some threads sit out
+                    if config.warmup_tries > 0 && tries == 0 {
+                        return None;
+                    }
+                    let mut rng = fastrand::Rng::with_seed(seed);
+                    warmup(input, config.warmup_seconds, tries, config.target, &mut rng)
+                })
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn search(
+    config: &GenOpts,
+    input: &Input,
+    schedule: &mut Schedule,
+    seconds: u64,
+    bound: &Mutex<Vec<ScoreLevel>>,
+    provenance: &Provenance,
+    shared: &Mutex<SharedBest>,
+    tag: &str,
+    rng: &mut fastrand::Rng,
+) {
+    match config.strategy {
+        SearchStrategy::Walk => random_walk(config, input, schedule, seconds, bound, provenance, shared, tag, rng),
+        SearchStrategy::Anneal | SearchStrategy::LateAcceptance => {
+            single_move_search(config, input, schedule, seconds, bound, provenance, shared, tag, rng)
+        }
+    }
+}
+
+// the reason to end a search before its time or iteration budget runs out, if
+// any, and whether it ends every search; a stall only ends the search that
+// stalled, since the others may still be improving
+fn stop_reason(
+    config: &GenOpts,
+    optimum: Option<ScoreTarget>,
+    best: &Score,
+    since_best: u64,
+    unit: &str,
+) -> Option<(String, bool)> {
+    if interrupted() {
+        Some(("interrupted".to_string(), true))
+    } else if optimum.is_some_and(|target| target.is_met(best)) {
+        Some((format!("reached the SAT-proven optimum {}", best), true))
+    } else if let Some(target) = config.target
+        && target.is_met(best)
+    {
+        Some((format!("reached the target {} with {}", target, best), true))
+    } else if config.stall > 0 && since_best >= config.stall {
+        Some((format!("no new best in {} {}", commas(config.stall), unit), false))
+    } else {
+        None
+    }
+}
+
+// the latest proven optimum prefix, and the score that meets it if stopping there is wanted
+fn read_bound(config: &GenOpts, bound: &Mutex<Vec<ScoreLevel>>) -> (Vec<ScoreLevel>, Option<ScoreTarget>) {
+    let prefix = bound.lock().unwrap().clone();
+    let optimum = (config.stop_at_optimum && !prefix.is_empty()).then(|| ScoreTarget::from_optimum_prefix(&prefix));
+    (prefix, optimum)
+}
+
+// the bound and how far a score is above it, for status lines
+fn describe_gap(score: &Score, prefix: &[ScoreLevel]) -> String {
+    if prefix.is_empty() {
+        return String::new();
+    }
+    let bound = Score { levels: ScoreTarget::from_optimum_prefix(prefix).levels };
+    match (0..PRIORITY_LEVELS).find(|&level| score.levels[level] != bound.levels[level]) {
+        Some(level) => format!(
+            ", bound {} through level {}, gap {} at level {}",
+            bound,
+            prefix.len() - 1,
+            score.levels[level] - bound.levels[level],
+            level
+        ),
+        None => format!(", bound {} met", bound),
+    }
+}
+
+// save a schedule if it improves on the best of every search;
+// returns false if saving failed and the search should quit
+fn share_best(
+    config: &GenOpts,
+    input: &Input,
+    schedule: &Schedule,
+    msg: String,
+    provenance: &Provenance,
+    shared: &Mutex<SharedBest>,
+    tag: &str,
+) -> bool {
+    // saves happen under the lock, and only for improvements on every search
+    let mut global = shared.lock().unwrap();
+    if schedule.score < global.schedule.score {
+        match save_schedule(&config.db_path, input, schedule, &msg, provenance, global.save_id) {
+            Ok(new_id) => {
+                global.save_id = Some(new_id);
+                global.schedule = schedule.clone();
+                global.comment = msg;
+            }
+            Err(e) => {
+                println!("{}quitting due to save error: {}", tag, e);
+                global.quit = true;
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[allow(clippy::too_many_arguments)]
+fn random_walk(
+    config: &GenOpts,
+    input: &Input,
+    schedule: &mut Schedule,
+    seconds: u64,
+    bound: &Mutex<Vec<ScoreLevel>>,
+    provenance: &Provenance,
+    shared: &Mutex<SharedBest>,
+    tag: &str,
+    rng: &mut fastrand::Rng,
+) {
     let mut best = schedule.clone();
     let mut walk = Walk::new(best.score);
     let mut bias = config.bias_min;
@@ -466,20 +780,79 @@ pub fn solve(
 
     let start = Instant::now();
     let mut last_seconds = 0;
+    let mut iterations: u64 = 0;
+    let mut iterations_at_rehome: u64 = 0;
+    let mut iterations_at_best: u64 = 0;
+
+    // the best this walk reached on its own, and when another walk got ahead of it
+    let mut own_best = best.score;
+    let mut behind_since: Option<u64> = None;
+
+    let mut optimum = read_bound(config, bound).1;
 
     // one big step per iteration
     loop {
+        // stop early once more work is pointless
+        if let Some((reason, everyone)) =
+            stop_reason(config, optimum, &best.score, iterations - iterations_at_best, "big steps")
+        {
+            println!("{}stopping: {}", tag, reason);
+            if everyone {
+                shared.lock().unwrap().quit = true;
+            }
+            break;
+        }
+
         // check if we need to report and adjust the bias
-        let elapsed = start.elapsed().as_secs();
+        let elapsed = if config.iterations > 0 {
+            if iterations >= config.iterations {
+                break;
+            }
+            iterations / BIG_STEPS_PER_TICK
+        } else {
+            start.elapsed().as_secs()
+        };
+        iterations += 1;
         if elapsed != last_seconds {
             last_seconds = elapsed;
 
+            // keep up with the best any walk has found
+            {
+                let global = shared.lock().unwrap();
+                if global.quit {
+                    break;
+                }
+                if global.schedule.score < best.score {
+                    best = global.schedule.clone();
+                    iterations_at_best = iterations;
+                }
+            }
+            let (prefix, latest) = read_bound(config, bound);
+            optimum = latest;
+
+            // move onto the best of every walk if this one has not caught up in time
+            if best.score < own_best {
+                let since = elapsed - *behind_since.get_or_insert(elapsed);
+                if since >= config.rehome_global_seconds {
+                    println!("{}behind another walk for {} seconds, rehoming onto its best", tag, commas(since));
+                    *schedule = best.clone();
+                    own_best = best.score;
+                    behind_since = None;
+                    walk.rehome(schedule.score);
+                    iterations_at_rehome = iterations;
+                    bias = config.bias_min;
+                    bias_delta = config.bias_step;
+                }
+            }
+
             // time for an update?
             if elapsed.is_multiple_of(config.update_seconds) {
                 println!(
-                    "{}: best {}, home {}, bias {}, ranged [{},{}] steps away from home since last report",
+                    "{}{}: best {}{}, home {}, bias {}, ranged [{},{}] steps away from home since last report",
+                    tag,
                     sec_to_string(elapsed),
                     best.score,
+                    describe_gap(&best.score, &prefix),
                     walk.best_score_since_rehome,
                     bias,
                     commas(walk.min_distance_this_interval),
@@ -490,7 +863,7 @@ pub fn solve(
                 walk.max_distance_this_interval = walk.distance();
                 walk.min_distance_this_interval = walk.distance();
             }
-            if elapsed >= seconds {
+            if config.iterations == 0 && elapsed >= seconds {
                 break;
             }
 
@@ -503,16 +876,17 @@ pub fn solve(
 
         // random walk: back up or move forward one big step
         // add bias to stepping backward if we have unplaced sections
-        let roll = fastrand::f64() * 100.0;
+        let roll = rng.f64() * 100.0;
         if walk.distance() == 0 || roll < 50.0 + bias {
             // make one big step forward
-            if !walk.step_forward(input, schedule) {
+            if !walk.step_forward(input, schedule, rng) {
                 // unrecoverable failure?
                 if schedule.score.is_zero() {
-                    println!("perfect score found, quitting search");
+                    println!("{}perfect score found, quitting search", tag);
+                    shared.lock().unwrap().quit = true;
                     break;
                 } else if walk.distance() == 0 {
-                    println!("cannot go forward or backward, giving up");
+                    println!("{}cannot go forward or backward, giving up", tag);
                     break;
                 }
 
@@ -521,31 +895,43 @@ pub fn solve(
                 walk.fall_back(input, schedule);
                 let post_distance = walk.distance();
                 println!(
-                    "random walk hit a wall, falling back from {} to {} steps from home",
+                    "{}random walk hit a wall, falling back from {} to {} steps from home",
+                    tag,
                     commas(pre_distance),
                     commas(post_distance)
                 );
 
                 // time to rehome?
-                let since_rehome = walk.time_of_rehome.elapsed().as_secs();
+                let since_rehome = if config.iterations > 0 {
+                    (iterations - iterations_at_rehome) / BIG_STEPS_PER_TICK
+                } else {
+                    walk.time_of_rehome.elapsed().as_secs()
+                };
                 if walk.best_score_since_rehome == best.score && since_rehome >= config.rehome_global_seconds
                     || walk.best_score_since_rehome != best.score && since_rehome >= config.rehome_local_seconds
                 {
                     if config.fallback {
-                        println!("no improvement for {} seconds, rehoming", commas(since_rehome));
+                        println!("{}no improvement for {} seconds, rehoming", tag, commas(since_rehome));
                     } else {
                         println!(
-                            "no improvement for {} seconds, running warmup for {}",
+                            "{}no improvement for {} seconds, running warmup for {}",
+                            tag,
                             commas(since_rehome),
                             sec_to_string(config.warmup_seconds)
                         );
-                        if let Some(new_schedule) = warmup(input, config.warmup_seconds) {
+                        if let Some(new_schedule) =
+                            warmup(input, config.warmup_seconds, config.warmup_tries, config.target, rng)
+                        {
                             *schedule = new_schedule;
                         } else {
-                            println!("failed to generate a schedule in the warmup stage, rehoming to fallback instead");
+                            println!(
+                                "{}failed to generate a schedule in the warmup stage, rehoming to fallback instead",
+                                tag
+                            );
                         };
                     }
                     walk.rehome(schedule.score);
+                    iterations_at_rehome = iterations;
                     bias = config.bias_min;
                     bias_delta = config.bias_step;
                 } else if bias_delta > 0.0 && bias > config.bias_min {
@@ -555,13 +941,17 @@ pub fn solve(
 
             if schedule.score < best.score {
                 if config.dfs_depth == 0 {
-                    println!("new best found {} steps from home", commas(walk.distance()));
+                    println!("{}new best found {} steps from home", tag, commas(walk.distance()));
                 } else {
-                    print!("new best found {} steps from home", commas(walk.distance()));
+                    print!("{}new best found {} steps from home", tag, commas(walk.distance()));
                     walk.try_dfs(input, schedule, config.dfs_depth, true);
                 }
                 best = schedule.clone();
+                own_best = best.score;
+                behind_since = None;
                 walk.rehome(schedule.score);
+                iterations_at_rehome = iterations;
+                iterations_at_best = iterations;
                 bias = config.bias_min;
                 bias_delta = config.bias_step;
                 let msg = format!(
@@ -570,21 +960,18 @@ pub fn solve(
                     commas(walk.big_step_count),
                     commas(walk.little_step_count)
                 );
-                match save_schedule(&config.db_path, input, schedule, &msg, *save_id) {
-                    Ok(new_id) => *save_id = Some(new_id),
-                    Err(e) => {
-                        println!("quitting due to save error: {}", e);
-                        return best;
-                    }
+                if !share_best(config, input, schedule, msg, provenance, shared, tag) {
+                    return;
                 }
             } else if schedule.score < walk.best_score_since_rehome {
                 if config.dfs_depth == 0 {
-                    println!("new local best found {} steps from home", commas(walk.distance()));
+                    println!("{}new local best found {} steps from home", tag, commas(walk.distance()));
                 } else {
-                    print!("new local best found {} steps from home", commas(walk.distance()));
+                    print!("{}new local best found {} steps from home", tag, commas(walk.distance()));
                     walk.try_dfs(input, schedule, config.dfs_depth, true);
                 }
                 walk.rehome(schedule.score);
+                iterations_at_rehome = iterations;
                 bias = config.bias_min;
                 bias_delta = config.bias_step;
             }
@@ -593,12 +980,266 @@ pub fn solve(
         }
     }
     println!(
-        "took {} big steps, average of {:.1} little steps each",
+        "{}took {} big steps, average of {:.1} little steps each",
+        tag,
         commas(walk.big_step_count),
         walk.little_step_count as f64 / walk.big_step_count as f64
     );
+}
 
-    best
+// annealing and late acceptance differ only in which worse moves they accept
+enum Acceptance {
+    // the temperature at each priority level when the search starts, which
+    // cools geometrically to the final fraction of it by the end
+    Anneal { start: [f64; PRIORITY_LEVELS], last: f64 },
+
+    // the current score as of each of the last so many moves
+    Late { history: Vec<Score> },
+}
+
+impl Acceptance {
+    fn accept(
+        &mut self,
+        current: Score,
+        candidate: Score,
+        iteration: u64,
+        progress: f64,
+        rng: &mut fastrand::Rng,
+    ) -> bool {
+        match self {
+            Acceptance::Anneal { start, last } => {
+                if candidate <= current {
+                    return true;
+                }
+
+                // only the most important level that got worse matters
+                let delta = candidate - current;
+                let level = delta.first_nonzero() as usize;
+                let temperature = start[level] * last.powf(progress.min(1.0));
+                rng.f64() < (-delta.levels[level] as f64 / temperature).exp()
+            }
+            Acceptance::Late { history } => {
+                let slot = iteration as usize % history.len();
+                let accepted = candidate <= current || candidate <= history[slot];
+                history[slot] = if accepted { candidate } else { current };
+                accepted
+            }
+        }
+    }
+
+    fn describe(&self, progress: f64) -> String {
+        match self {
+            Acceptance::Anneal { last, .. } => {
+                format!("cooled to {:.2}% of starting temperature, ", 100.0 * last.powf(progress.min(1.0)))
+            }
+            Acceptance::Late { .. } => String::new(),
+        }
+    }
+}
+
+// the number of random moves sampled to set the starting temperatures
+const TEMPERATURE_SAMPLES: usize = 1000;
+
+// start each level hot enough that a typical move that makes it worse
+// is accepted half the time
+fn starting_temperatures(
+    input: &Input,
+    schedule: &mut Schedule,
+    movable: &[usize],
+    rng: &mut fastrand::Rng,
+) -> [f64; PRIORITY_LEVELS] {
+    let zero = Score::new();
+    let mut total = [0.0; PRIORITY_LEVELS];
+    let mut count = [0; PRIORITY_LEVELS];
+    for _ in 0..TEMPERATURE_SAMPLES {
+        let Some(candidate) = random_move(input, schedule, movable, rng) else {
+            continue;
+        };
+        let delta = try_one_move(input, schedule, &candidate);
+        if delta > zero {
+            let level = delta.first_nonzero() as usize;
+            total[level] += delta.levels[level] as f64;
+            count[level] += 1;
+        }
+    }
+    std::array::from_fn(|level| {
+        let typical = if count[level] > 0 { total[level] / count[level] as f64 } else { 1.0 };
+        typical / std::f64::consts::LN_2
+    })
+}
+
+// a random single-section move, favoring sections that contribute to the score;
+// None if it would not move anything or would displace a pinned section
+fn random_move(input: &Input, schedule: &Schedule, movable: &[usize], rng: &mut fastrand::Rng) -> Option<Move> {
+    let penalized: Vec<usize> =
+        movable.iter().copied().filter(|&section| !schedule.placements[section].score.is_zero()).collect();
+    let section = if !penalized.is_empty() && rng.bool() {
+        penalized[rng.usize(..penalized.len())]
+    } else {
+        movable[rng.usize(..movable.len())]
+    };
+
+    let time_slots = &input.sections[section].time_slots;
+    let time_slot = time_slots[rng.usize(..time_slots.len())].time_slot;
+    let rooms = rooms_adapter(&input.sections[section].rooms);
+    let room = rooms[rng.usize(..rooms.len())];
+    if schedule.placements[section].time_slot == Some(time_slot) && schedule.placements[section].room == room {
+        return None;
+    }
+    if let Some((_, true)) = schedule.has_hard_conflict(input, section, time_slot, &room, &[]) {
+        return None;
+    }
+    Some(Move::Single { section, time_slot, room })
+}
+
+// simulated annealing or late-acceptance hill climbing: propose one random
+// single-section move per iteration and keep it if the acceptance rule allows
+#[allow(clippy::too_many_arguments)]
+fn single_move_search(
+    config: &GenOpts,
+    input: &Input,
+    schedule: &mut Schedule,
+    seconds: u64,
+    bound: &Mutex<Vec<ScoreLevel>>,
+    provenance: &Provenance,
+    shared: &Mutex<SharedBest>,
+    tag: &str,
+    rng: &mut fastrand::Rng,
+) {
+    let movable: Vec<usize> = (0..input.sections.len())
+        .filter(|&section| !input.sections[section].is_pinned() && !input.sections[section].time_slots.is_empty())
+        .collect();
+    if movable.is_empty() {
+        println!("{}no sections can be moved, giving up", tag);
+        return;
+    }
+
+    let mut acceptance = match config.strategy {
+        SearchStrategy::Anneal => Acceptance::Anneal {
+            start: starting_temperatures(input, schedule, &movable, rng),
+            last: config.anneal_final,
+        },
+        _ => Acceptance::Late { history: vec![schedule.score; config.late_length.max(1)] },
+    };
+
+    let mut best = schedule.clone();
+    let start = Instant::now();
+    let mut last_seconds = 0;
+    let mut iterations: u64 = 0;
+    let mut iterations_at_best: u64 = 0;
+    let mut tried: u64 = 0;
+    let mut accepted: u64 = 0;
+
+    // the best this search reached on its own, and when another search got ahead of it
+    let mut own_best = best.score;
+    let mut behind_since: Option<u64> = None;
+
+    let mut optimum = read_bound(config, bound).1;
+
+    // one proposed move per iteration
+    loop {
+        // stop early once more work is pointless
+        if let Some((reason, everyone)) =
+            stop_reason(config, optimum, &best.score, iterations - iterations_at_best, "moves")
+        {
+            println!("{}stopping: {}", tag, reason);
+            if everyone {
+                shared.lock().unwrap().quit = true;
+            }
+            break;
+        }
+
+        let (elapsed, progress) = if config.iterations > 0 {
+            if iterations >= config.iterations {
+                break;
+            }
+            (iterations / MOVES_PER_TICK, iterations as f64 / config.iterations as f64)
+        } else {
+            let elapsed = start.elapsed();
+            (elapsed.as_secs(), elapsed.as_secs_f64() / seconds.max(1) as f64)
+        };
+        iterations += 1;
+        if elapsed != last_seconds {
+            last_seconds = elapsed;
+
+            // keep up with the best any search has found
+            {
+                let global = shared.lock().unwrap();
+                if global.quit {
+                    break;
+                }
+                if global.schedule.score < best.score {
+                    best = global.schedule.clone();
+                    iterations_at_best = iterations;
+                }
+            }
+            let (prefix, latest) = read_bound(config, bound);
+            optimum = latest;
+
+            // move onto the best of every search if this one has not caught up in time
+            if best.score < own_best {
+                let since = elapsed - *behind_since.get_or_insert(elapsed);
+                if since >= config.rehome_global_seconds {
+                    println!("{}behind another search for {} seconds, moving onto its best", tag, commas(since));
+                    *schedule = best.clone();
+                    own_best = best.score;
+                    behind_since = None;
+                    if let Acceptance::Late { history } = &mut acceptance {
+                        history.fill(schedule.score);
+                    }
+                }
+            }
+
+            // time for an update?
+            if elapsed.is_multiple_of(config.update_seconds) {
+                println!(
+                    "{}{}: best {}{}, current {}, {}accepted {} of {} moves since last report",
+                    tag,
+                    sec_to_string(elapsed),
+                    best.score,
+                    describe_gap(&best.score, &prefix),
+                    schedule.score,
+                    acceptance.describe(progress),
+                    commas(accepted),
+                    commas(tried),
+                );
+                tried = 0;
+                accepted = 0;
+            }
+            if config.iterations == 0 && elapsed >= seconds {
+                break;
+            }
+        }
+
+        let Some(candidate) = random_move(input, schedule, &movable, rng) else {
+            continue;
+        };
+        tried += 1;
+        let delta = try_one_move(input, schedule, &candidate);
+        if !acceptance.accept(schedule.score, schedule.score + delta, iterations, progress, rng) {
+            continue;
+        }
+        accepted += 1;
+        let _undo = apply_move(input, schedule, &candidate);
+
+        if schedule.score < best.score {
+            println!("{}new best found after {} moves", tag, commas(iterations));
+            best = schedule.clone();
+            own_best = best.score;
+            behind_since = None;
+            iterations_at_best = iterations;
+            let msg = format!(
+                "found with {} after {} seconds and {} moves",
+                if config.strategy == SearchStrategy::Anneal { "simulated annealing" } else { "late acceptance" },
+                commas(start.elapsed().as_secs()),
+                commas(iterations)
+            );
+            if !share_best(config, input, schedule, msg, provenance, shared, tag) {
+                return;
+            }
+        }
+    }
+    println!("{}tried {} moves", tag, commas(iterations));
 }
 
 pub struct Walk {
@@ -650,10 +1291,10 @@ impl Walk {
         self.time_of_rehome = Instant::now();
     }
 
-    pub fn step_forward(&mut self, input: &Input, schedule: &mut Schedule) -> bool {
+    pub fn step_forward(&mut self, input: &Input, schedule: &mut Schedule, rng: &mut fastrand::Rng) -> bool {
         let pre_steps = self.step_log.len();
 
-        if !step_down(input, schedule, self) {
+        if !step_down(input, schedule, self, rng) {
             return false;
         }
 
@@ -682,7 +1323,7 @@ impl Walk {
                 break;
             }
             post_steps = latest;
-            if !repeat {
+            if !repeat || interrupted() {
                 break;
             }
         }
@@ -716,7 +1357,7 @@ impl Walk {
     }
 }
 
-fn rooms_adapter(rooms: &[RoomWithOptionalPriority]) -> Vec<Option<usize>> {
+pub fn rooms_adapter(rooms: &[RoomWithOptionalPriority]) -> Vec<Option<usize>> {
     if rooms.is_empty() {
         vec![None]
     } else {
@@ -724,15 +1365,68 @@ fn rooms_adapter(rooms: &[RoomWithOptionalPriority]) -> Vec<Option<usize>> {
     }
 }
 
-pub fn warmup(input: &Input, seconds: u64) -> Option<Schedule> {
+// place just the given sections, leaving everything else where it is: the
+// most constrained section goes first, each into the open room and time that
+// scores best; returns the sections that had nowhere to go
+pub fn repair_sections(input: &Input, schedule: &mut Schedule, sections: &[usize]) -> Vec<usize> {
+    let mut pending: Vec<usize> =
+        sections.iter().copied().filter(|&section| schedule.placements[section].time_slot.is_none()).collect();
+    let mut stuck = Vec::new();
+    while !pending.is_empty() {
+        // gather the open placements for each pending section
+        let options: Vec<Vec<(usize, Option<usize>)>> = pending
+            .iter()
+            .map(|&section| {
+                let mut list = Vec::new();
+                for &TimeSlotWithOptionalPriority { time_slot, .. } in &input.sections[section].time_slots {
+                    for maybe_room in rooms_adapter(&input.sections[section].rooms) {
+                        if schedule.has_hard_conflict(input, section, time_slot, &maybe_room, &[]).is_none() {
+                            list.push((time_slot, maybe_room));
+                        }
+                    }
+                }
+                list
+            })
+            .collect();
+        let (i, _) = options.iter().enumerate().min_by_key(|(_, list)| list.len()).unwrap();
+        let section = pending.swap_remove(i);
+        let mut best: Option<(Score, usize, Option<usize>)> = None;
+        for &(time_slot, maybe_room) in &options[i] {
+            let delta = speculative_move_section(input, schedule, section, time_slot, &maybe_room);
+            if best.as_ref().is_none_or(|(score, _, _)| delta < *score) {
+                best = Some((delta, time_slot, maybe_room));
+            }
+        }
+        match best {
+            Some((_, time_slot, maybe_room)) => {
+                let _undo = move_section(input, schedule, section, time_slot, &maybe_room);
+            }
+            None => stuck.push(section),
+        }
+    }
+    stuck.sort_unstable();
+    stuck
+}
+
+// build schedules greedily with random choices, climbing from the promising
+// ones, for the given number of seconds or, when tries is nonzero, for exactly
+// that many schedules; stops early on a perfect score or one meeting the target
+pub fn warmup(
+    input: &Input,
+    seconds: u64,
+    tries: u64,
+    target: Option<ScoreTarget>,
+    rng: &mut fastrand::Rng,
+) -> Option<Schedule> {
     let start = Instant::now();
     let mut best = None;
     let mut best_pre_climb_score = None;
     let mut count = 0;
     let taboo = Vec::new();
-    while start.elapsed().as_secs() < seconds {
+    while !interrupted() && if tries > 0 { count < tries } else { start.elapsed().as_secs() < seconds } {
         count += 1;
         let mut schedule = Schedule::new(input);
+        place_at_baseline(input, &mut schedule);
         while schedule.score.unplaced() > 0 {
             // find the most-constrained section
             // and the number of room/time combos available to it
@@ -775,7 +1469,7 @@ pub fn warmup(input: &Input, seconds: u64) -> Option<Schedule> {
             };
 
             // randomly choose one of the available placements
-            let winner = fastrand::usize(1..=options);
+            let winner = rng.usize(1..=options);
 
             // find that placement
             let mut count = 0;
@@ -818,6 +1512,12 @@ pub fn warmup(input: &Input, seconds: u64) -> Option<Schedule> {
                             println!("perfect score found, quitting warmup");
                             break;
                         }
+                        if let Some(target) = target
+                            && target.is_met(&score)
+                        {
+                            println!("target {} reached, quitting warmup", target);
+                            break;
+                        }
                     }
                 }
             }
@@ -828,11 +1528,203 @@ pub fn warmup(input: &Input, seconds: u64) -> Option<Schedule> {
     best
 }
 
+// in a re-solve, start with every section that still fits where the baseline put it
+fn place_at_baseline(input: &Input, schedule: &mut Schedule) {
+    for (section, elt) in input.sections.iter().enumerate() {
+        let Some((time_slot, room)) = elt.baseline else {
+            continue;
+        };
+        if elt.is_pinned()
+            || !elt.time_slots.iter().any(|option| option.time_slot == time_slot)
+            || !rooms_adapter(&elt.rooms).contains(&room)
+        {
+            continue;
+        }
+        if schedule.has_hard_conflict(input, section, time_slot, &room, &[]).is_none() {
+            let _undo = move_section(input, schedule, section, time_slot, &room);
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Move {
-    pub section: usize,
-    pub time_slot: Option<usize>,
-    pub room: Option<usize>,
+pub enum Move {
+    // put one section in a new time slot and room, displacing anything in the way
+    Single { section: usize, time_slot: usize, room: Option<usize> },
+
+    // two placed sections exchange time slots, each keeping its room
+    SwapTimeSlots(usize, usize),
+
+    // two placed sections exchange rooms, each keeping its time slot
+    SwapRooms(usize, usize),
+
+    // every section in the chain trades between the two time slots,
+    // keeping its room; see kempe_chain
+    Kempe { sections: Vec<usize>, time_slots: (usize, usize) },
+}
+
+impl Move {
+    // the section the move was chosen for
+    pub fn section(&self) -> usize {
+        match self {
+            Move::Single { section, .. } => *section,
+            Move::SwapTimeSlots(a, _) | Move::SwapRooms(a, _) => *a,
+            Move::Kempe { sections, .. } => sections[0],
+        }
+    }
+
+    // every section the move places, not counting any it displaces
+    pub fn sections(&self) -> Vec<usize> {
+        match self {
+            Move::Single { section, .. } => vec![*section],
+            Move::SwapTimeSlots(a, b) | Move::SwapRooms(a, b) => vec![*a, *b],
+            Move::Kempe { sections, .. } => sections.clone(),
+        }
+    }
+
+    // where the move puts each section it places, given where they are now;
+    // empty if a swap involves an unplaced section
+    pub fn targets(&self, schedule: &Schedule) -> Vec<(usize, usize, Option<usize>)> {
+        let placements = &schedule.placements;
+        match self {
+            &Move::Single { section, time_slot, room } => vec![(section, time_slot, room)],
+            &Move::SwapTimeSlots(a, b) => {
+                let (Some(a_time_slot), Some(b_time_slot)) = (placements[a].time_slot, placements[b].time_slot) else {
+                    return Vec::new();
+                };
+                vec![(a, b_time_slot, placements[a].room), (b, a_time_slot, placements[b].room)]
+            }
+            &Move::SwapRooms(a, b) => {
+                let (Some(a_time_slot), Some(b_time_slot)) = (placements[a].time_slot, placements[b].time_slot) else {
+                    return Vec::new();
+                };
+                vec![(a, a_time_slot, placements[b].room), (b, b_time_slot, placements[a].room)]
+            }
+            Move::Kempe { sections, time_slots: (from, to) } => sections
+                .iter()
+                .map(|&section| {
+                    let time_slot = if placements[section].time_slot == Some(*from) { *to } else { *from };
+                    (section, time_slot, placements[section].room)
+                })
+                .collect(),
+        }
+    }
+}
+
+// the swaps and Kempe chain shifts that involve a placed section, leaving out
+// any that would move or displace a pinned or taboo section; swaps are limited
+// to trades that a single move could not make without displacing the other
+// section: time slots in the same room, and rooms at overlapping times
+pub fn compound_moves(input: &Input, schedule: &Schedule, section: usize, taboo: &[usize]) -> Vec<Move> {
+    let mut moves = Vec::new();
+    let mut push = |candidate: Move| {
+        let displaces_taboo = candidate.targets(schedule).into_iter().any(|(section, time_slot, room)| {
+            matches!(schedule.has_hard_conflict(input, section, time_slot, &room, taboo), Some((_, true)))
+        });
+        if !displaces_taboo {
+            moves.push(candidate);
+        }
+    };
+    let Some(time_slot) = schedule.placements[section].time_slot else {
+        return moves;
+    };
+    let room = schedule.placements[section].room;
+    let allows_time_slot = |section: usize, time_slot: usize| {
+        input.sections[section].time_slots.iter().any(|elt| elt.time_slot == time_slot)
+    };
+    let allows_room = |section: usize, room: usize| input.sections[section].rooms.iter().any(|elt| elt.room == room);
+
+    for other in 0..input.sections.len() {
+        if other == section || taboo.contains(&other) || input.sections[other].is_pinned() {
+            continue;
+        }
+        let Some(other_time_slot) = schedule.placements[other].time_slot else {
+            continue;
+        };
+        let other_room = schedule.placements[other].room;
+        if room == other_room
+            && other_time_slot != time_slot
+            && allows_time_slot(section, other_time_slot)
+            && allows_time_slot(other, time_slot)
+        {
+            push(Move::SwapTimeSlots(section, other));
+        }
+        if let (Some(room), Some(other_room)) = (room, other_room)
+            && room != other_room
+            && input.time_slot_conflicts[time_slot][other_time_slot]
+            && allows_room(section, other_room)
+            && allows_room(other, room)
+        {
+            push(Move::SwapRooms(section, other));
+        }
+    }
+
+    // a chain of one is just a single move that keeps the room
+    for &TimeSlotWithOptionalPriority { time_slot: other_time_slot, .. } in &input.sections[section].time_slots {
+        if other_time_slot == time_slot {
+            continue;
+        }
+        if let Some(sections) = kempe_chain(input, schedule, section, time_slot, other_time_slot, taboo)
+            && sections.len() > 1
+        {
+            push(Move::Kempe { sections, time_slots: (time_slot, other_time_slot) });
+        }
+    }
+    moves
+}
+
+// the Kempe chain through a section: moving it from one time slot to another
+// means any section it would collide with there (a hard conflict at an
+// overlapping time, or another section in its room) must move the opposite
+// way, and so on, until the chain closes; None if the chain reaches a pinned
+// or taboo section, one not allowed in its new time slot, or one in some
+// third time slot that the shift would displace
+fn kempe_chain(
+    input: &Input,
+    schedule: &Schedule,
+    section: usize,
+    from: usize,
+    to: usize,
+    taboo: &[usize],
+) -> Option<Vec<usize>> {
+    let mut chain = vec![section];
+    let mut i = 0;
+    while i < chain.len() {
+        let elt = chain[i];
+        i += 1;
+        let target = if schedule.placements[elt].time_slot == Some(from) { to } else { from };
+        if !input.sections[elt].time_slots.iter().any(|ts| ts.time_slot == target) {
+            return None;
+        }
+
+        let mut collisions = Vec::new();
+        for &other in &input.sections[elt].hard_conflicts {
+            if let Some(other_time_slot) = schedule.placements[other].time_slot {
+                collisions.push((other, other_time_slot));
+            }
+        }
+        if let Some(room) = schedule.placements[elt].room {
+            for &TimeSlotPlacement { time_slot: other_time_slot, section: other } in
+                &schedule.room_placements[room].used_time_slots
+            {
+                if other != elt {
+                    collisions.push((other, other_time_slot));
+                }
+            }
+        }
+        for (other, other_time_slot) in collisions {
+            if !input.time_slot_conflicts[target][other_time_slot] || chain.contains(&other) {
+                continue;
+            }
+            if other_time_slot != from && other_time_slot != to {
+                return None;
+            }
+            if taboo.contains(&other) || input.sections[other].is_pinned() {
+                return None;
+            }
+            chain.push(other);
+        }
+    }
+    Some(chain)
 }
 
 pub fn climb(input: &Input, schedule: &mut Schedule, log: &mut Vec<PlacementLog>, taboo: &[usize]) {
@@ -864,7 +1756,7 @@ pub fn climb(input: &Input, schedule: &mut Schedule, log: &mut Vec<PlacementLog>
             }
 
             // skip taboo moves
-            if taboo.contains(&section) {
+            if taboo.contains(&section) || input.sections[section].is_pinned() {
                 continue;
             }
 
@@ -890,7 +1782,7 @@ pub fn climb(input: &Input, schedule: &mut Schedule, log: &mut Vec<PlacementLog>
                         }
                     };
 
-                    let candidate = Move { section, time_slot: Some(time_slot), room };
+                    let candidate = Move::Single { section, time_slot, room };
                     let delta = try_one_move(input, schedule, &candidate);
 
                     // only consider moves that were improvements
@@ -903,26 +1795,23 @@ pub fn climb(input: &Input, schedule: &mut Schedule, log: &mut Vec<PlacementLog>
         }
 
         // did we find an improving move?
-        let Some(Move { section, time_slot: Some(ts), room }) = best_move else {
+        let Some(best_move) = best_move else {
             // no viable moves found
             break;
         };
 
         // apply the move, but do not add it to the taboo list
-        let log_entry = move_section(input, schedule, section, ts, &room);
+        let log_entry = apply_move(input, schedule, &best_move);
         log.push(log_entry);
     }
 }
 
-// try a single section move then undo it, and return the score delta it created
+// try a move then undo it, and return the score delta it created
 pub fn try_one_move(input: &Input, schedule: &mut Schedule, candidate_move: &Move) -> Score {
-    let &Move { section, time_slot: Some(ts), room } = candidate_move else {
-        panic!("try_one_move called with no time slot");
-    };
-    speculative_move_section(input, schedule, section, ts, &room)
+    speculative_move(input, schedule, candidate_move)
 }
 
-pub fn step_down(input: &Input, schedule: &mut Schedule, walk: &mut Walk) -> bool {
+pub fn step_down(input: &Input, schedule: &mut Schedule, walk: &mut Walk, rng: &mut fastrand::Rng) -> bool {
     // do not prioritize scores that we know we cannot improve
     let first_non_optimum_priority = schedule.first_non_optimum_priority();
 
@@ -932,18 +1821,18 @@ pub fn step_down(input: &Input, schedule: &mut Schedule, walk: &mut Walk) -> boo
     let mut candidates = Vec::new();
     for section in 0..input.sections.len() {
         // skip taboo moves
-        if walk.taboo.contains(&section) {
+        if walk.taboo.contains(&section) || input.sections[section].is_pinned() {
             continue;
         }
 
         // try each time slot
         'time_loop: for &TimeSlotWithOptionalPriority { time_slot, .. } in &input.sections[section].time_slots {
             for room in rooms_adapter(&input.sections[section].rooms) {
-                let candidate = Move { section, time_slot: Some(time_slot), room };
+                let candidate = Move::Single { section, time_slot, room };
 
                 // the current location is off limits, i.e., no moves that do not move anything
-                if schedule.placements[section].time_slot == candidate.time_slot
-                    && schedule.placements[section].room == candidate.room
+                if schedule.placements[section].time_slot == Some(time_slot)
+                    && schedule.placements[section].room == room
                 {
                     continue;
                 }
@@ -980,32 +1869,31 @@ pub fn step_down(input: &Input, schedule: &mut Schedule, walk: &mut Walk) -> boo
     let mut candidate = None;
     for chunk in candidates.chunk_by(|(a, _), (b, _)| a == b) {
         // toss a coin at each priority level to use it or move on
-        if fastrand::bool() {
+        if rng.bool() {
             // group this priority level by section
-            let by_section: Vec<&[(u8, Move)]> =
-                chunk.chunk_by(|(_, Move { section: a, .. }), (_, Move { section: b, .. })| a == b).collect();
+            let by_section: Vec<&[(u8, Move)]> = chunk.chunk_by(|(_, a), (_, b)| a.section() == b.section()).collect();
 
             // pick a section
-            let by_section_index = fastrand::usize(0..by_section.len());
+            let by_section_index = rng.usize(0..by_section.len());
             let one_section = &by_section[by_section_index];
 
             // pick a placement for that section
-            let index = fastrand::usize(0..one_section.len());
+            let index = rng.usize(0..one_section.len());
             candidate = Some(one_section[index].1.clone());
             break;
         }
     }
     if candidate.is_none() {
-        let index = fastrand::usize(0..candidates.len());
+        let index = rng.usize(0..candidates.len());
         candidate = Some(candidates[index].1.clone());
     }
-    let Some(Move { section, time_slot: Some(ts), room }) = candidate else {
+    let Some(candidate) = candidate else {
         return false;
     };
 
     // apply the move and add the section that was moved to the taboo list
-    walk.taboo.push(section);
-    let log_entry = move_section(input, schedule, section, ts, &room);
+    walk.taboo.push(candidate.section());
+    let log_entry = apply_move(input, schedule, &candidate);
     walk.step_log.push(log_entry);
 
     true
@@ -1024,8 +1912,7 @@ pub fn depth_first_search(input: &Input, schedule: &mut Schedule, walk: &mut Wal
 
     // apply the moves if any
     for elt in best_moves {
-        let Move { section, time_slot, room } = elt;
-        walk.step_log.push(move_section(input, schedule, section, time_slot.unwrap(), &room));
+        walk.step_log.push(apply_move(input, schedule, &elt));
     }
 }
 
@@ -1040,8 +1927,16 @@ fn dfs_helper(
 ) {
     // for each section
     for section in 0..input.sections.len() {
+        // on an interrupt, settle for the best moves found so far
+        if interrupted() {
+            return;
+        }
+
         // ignore taboo sections and sections with zero scores
-        if walk.taboo.contains(&section) || schedule.placements[section].score.is_zero() {
+        if walk.taboo.contains(&section)
+            || input.sections[section].is_pinned()
+            || schedule.placements[section].score.is_zero()
+        {
             continue;
         }
 
@@ -1075,36 +1970,139 @@ fn dfs_helper(
                     }
                 };
 
-                // make the move
-                let candidate = Move { section, time_slot: Some(time_slot), room };
-                if depth == 0 {
-                    // special case for leaf of search
-                    let delta = try_one_move(input, schedule, &candidate);
-                    if schedule.score + delta < *best_score {
-                        current.push(candidate);
-                        *best_moves = current.clone();
-                        *best_score = schedule.score + delta;
-                        current.pop();
-                    }
-                } else {
-                    walk.taboo.push(section);
-                    walk.step_log.push(move_section(input, schedule, section, time_slot, &room));
-                    current.push(candidate);
-
-                    // improvement?
-                    if schedule.score < *best_score {
-                        *best_moves = current.clone();
-                        *best_score = schedule.score;
-                    }
-
-                    // recursive call
-                    dfs_helper(input, schedule, walk, depth - 1, best_moves, best_score, current);
+                let candidate = Move::Single { section, time_slot, room };
+                dfs_step(input, schedule, walk, depth, best_moves, best_score, current, candidate);
+            }
+        }
 
-                    // undo the move
-                    walk.taboo.pop();
-                    revert_move(input, schedule, &walk.step_log.pop().unwrap());
-                    current.pop();
+        // try trading places with other sections
+        for candidate in compound_moves(input, schedule, section, &walk.taboo) {
+            // at a leaf, the other sections moved must help too
+            if depth == 0 {
+                let mut floor = schedule.score;
+                for elt in candidate.sections() {
+                    floor -= schedule.placements[elt].score;
                 }
+                if floor >= *best_score {
+                    continue;
+                }
+            }
+            dfs_step(input, schedule, walk, depth, best_moves, best_score, current, candidate);
+        }
+    }
+}
+
+// try one candidate move in the search
+#[allow(clippy::too_many_arguments)]
+fn dfs_step(
+    input: &Input,
+    schedule: &mut Schedule,
+    walk: &mut Walk,
+    depth: usize,
+    best_moves: &mut Vec<Move>,
+    best_score: &mut Score,
+    current: &mut Vec<Move>,
+    candidate: Move,
+) {
+    if depth == 0 {
+        // special case for leaf of search
+        let delta = try_one_move(input, schedule, &candidate);
+        if schedule.score + delta < *best_score {
+            current.push(candidate);
+            *best_moves = current.clone();
+            *best_score = schedule.score + delta;
+            current.pop();
+        }
+        return;
+    }
+
+    // make the move
+    let moved = candidate.sections();
+    walk.taboo.extend(&moved);
+    walk.step_log.push(apply_move(input, schedule, &candidate));
+    current.push(candidate);
+
+    // improvement?
+    if schedule.score < *best_score {
+        *best_moves = current.clone();
+        *best_score = schedule.score;
+    }
+
+    // recursive call
+    dfs_helper(input, schedule, walk, depth - 1, best_moves, best_score, current);
+
+    // undo the move
+    walk.taboo.truncate(walk.taboo.len() - moved.len());
+    revert_move(input, schedule, &walk.step_log.pop().unwrap());
+    current.pop();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::faculty_preferences::FacultyPreferencePriorityPolicy;
+
+    fn section(name: &str, time_slots: &[usize], pinned: Option<(usize, Option<usize>)>) -> Section {
+        Section {
+            name: name.to_string(),
+            rooms: vec![RoomWithOptionalPriority { room: 0, priority: None }],
+            time_slots: time_slots
+                .iter()
+                .map(|&time_slot| TimeSlotWithOptionalPriority { time_slot, priority: None })
+                .collect(),
+            faculty: vec![],
+            enrollment: None,
+            pinned,
+            baseline: None,
+            background: false,
+            hard_conflicts: vec![],
+            criteria: vec![],
+            neighbors: vec![],
+        }
+    }
+
+    #[test]
+    fn compound_moves_never_unplace_a_pinned_section() {
+        // A and B share a room and could trade time slots, but A conflicts
+        // with P, which is pinned in B's time slot in a room of its own
+        let monday = Days::parse("M").unwrap();
+        let mut sections =
+            vec![section("A", &[0, 1], None), section("B", &[0, 1], None), section("P", &[1], Some((1, Some(1))))];
+        sections[2].rooms = vec![RoomWithOptionalPriority { room: 1, priority: None }];
+        sections[0].hard_conflicts = vec![2];
+        sections[2].hard_conflicts = vec![0];
+        let input = Input {
+            term_name: "test".to_string(),
+            departments: vec![],
+            background_id: None,
+            baseline: None,
+            rooms: vec![Room { name: "R".to_string(), capacity: 30 }, Room { name: "S".to_string(), capacity: 30 }],
+            time_slots: (0..2)
+                .map(|index| TimeSlot {
+                    name: format!("T{index}"),
+                    days: monday,
+                    start_time: Time::new(index * 60),
+                    duration: Duration::new(50),
+                })
+                .collect(),
+            faculty: vec![],
+            sections,
+            criteria: vec![],
+            faculty_preference_priority_policy: FacultyPreferencePriorityPolicy::Stated,
+            time_slot_conflicts: vec![vec![true, false], vec![false, true]],
+        };
+
+        let mut schedule = Schedule::new(&input);
+        move_section(&input, &mut schedule, 0, 0, &Some(0));
+        move_section(&input, &mut schedule, 1, 1, &Some(0));
+        assert!(schedule.is_placed(2));
+        assert_eq!(schedule.placements[2].room, Some(1));
+
+        for section in 0..2 {
+            for candidate in compound_moves(&input, &schedule, section, &[]) {
+                let mut copy = schedule.clone();
+                apply_move(&input, &mut copy, &candidate);
+                assert!(copy.is_placed(2), "moving {:?} unplaced the pinned section", candidate.sections());
             }
         }
     }